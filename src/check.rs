@@ -0,0 +1,118 @@
+use std::{fmt::Display, fs, io::ErrorKind, path::Path};
+
+use crate::solution::Answers;
+
+/// Recorded answers for a day, read from `dayN.txt` in the answers directory.
+/// The first line is the part 1 answer and the remaining lines are the part 2
+/// answer, which may span several lines.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Expected {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail(String),
+    Missing,
+}
+
+impl Expected {
+    pub fn load<P: AsRef<Path>>(dir: P, day: i32) -> Result<Self, String> {
+        let path = dir.as_ref().join(format!("day{}.txt", day));
+        match fs::read_to_string(&path) {
+            Ok(contents) => Ok(Expected::parse(&contents)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Expected::default()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn parse(contents: &str) -> Self {
+        let (part1, part2) = contents.split_once('\n').unwrap_or((contents, ""));
+
+        let non_empty = |s: &str| {
+            if s.trim().is_empty() {
+                None
+            } else {
+                Some(s.to_string())
+            }
+        };
+
+        Expected {
+            part1: non_empty(part1),
+            part2: non_empty(part2),
+        }
+    }
+
+    pub fn check(&self, answers: &Answers) -> [Status; 2] {
+        [
+            compare(self.part1.as_deref(), &answers.part1),
+            compare(self.part2.as_deref(), &answers.part2),
+        ]
+    }
+}
+
+fn normalise(answer: &str) -> Vec<&str> {
+    let mut lines: Vec<_> = answer
+        .lines()
+        .map(|l| l.trim_end())
+        .skip_while(|l| l.is_empty())
+        .collect();
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+    lines
+}
+
+fn compare(expected: Option<&str>, actual: &str) -> Status {
+    match expected {
+        None => Status::Missing,
+        Some(expected) if normalise(expected) == normalise(actual) => Status::Pass,
+        Some(expected) => Status::Fail(expected.trim_matches('\n').to_string()),
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Pass => write!(f, "PASS"),
+            Status::Fail(expected) if expected.contains('\n') => write!(f, "FAIL"),
+            Status::Fail(expected) => write!(f, "FAIL (expected {})", expected),
+            Status::Missing => write!(f, "MISSING"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Expected, Status};
+    use crate::solution::Answers;
+
+    #[test]
+    fn check() {
+        let expected = Expected::parse("24000\n45000\n");
+        let answers = |part1: &str, part2: &str| Answers {
+            part1: part1.to_string(),
+            part2: part2.to_string(),
+            details: Vec::new(),
+        };
+
+        assert_eq!(
+            expected.check(&answers("24000", "45000")),
+            [Status::Pass, Status::Pass]
+        );
+        assert_eq!(
+            expected.check(&answers("24000", "1")),
+            [Status::Pass, Status::Fail("45000".to_string())]
+        );
+        assert_eq!(
+            Expected::parse("2=-1=0\n").check(&answers("2=-1=0", "")),
+            [Status::Pass, Status::Missing]
+        );
+        assert_eq!(
+            Expected::parse("0\n\n##  \n#\n").check(&answers("0", "##\n#  ")),
+            [Status::Pass, Status::Pass]
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    hash::Hash,
+};
+
+/// Where a sequence of states starts repeating: the state after `start`
+/// steps is the same as the one after `start + period` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The step at or before the cycle's first repeat that is in the same
+    /// state as step `n`, and how many whole periods lie between them.
+    pub fn reduce(&self, n: u64) -> (usize, u64) {
+        let (start, period) = (self.start as u64, self.period as u64);
+        if n < start {
+            return (n as usize, 0);
+        }
+
+        let periods = (n - start) / period;
+        ((n - periods * period) as usize, periods)
+    }
+}
+
+/// Runs `step` on `state` until `key` gives a value it has given before, or
+/// `limit` steps have passed without one, or `key` gives up with `None`.
+pub fn find_cycle<S, K, F, H>(state: &mut S, limit: usize, mut step: F, mut key: H) -> Option<Cycle>
+where
+    F: FnMut(&mut S),
+    H: FnMut(&S) -> Option<K>,
+    K: Hash + Eq,
+{
+    let mut seen = HashMap::new();
+
+    for i in 0..=limit {
+        match seen.entry(key(state)?) {
+            Entry::Occupied(entry) => {
+                let start = *entry.get();
+                return Some(Cycle {
+                    start,
+                    period: i - start,
+                });
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(i);
+            }
+        }
+        step(state);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_cycle, Cycle};
+
+    #[test]
+    fn cycle() {
+        // 3, 10, 5, 16, 8, 4, 2, 1, 4, 2, 1, ...
+        let collatz = |n: &mut u64| {
+            *n = if n.is_multiple_of(2) {
+                *n / 2
+            } else {
+                3 * *n + 1
+            }
+        };
+
+        let cycle = find_cycle(&mut 3, 100, collatz, |n| Some(*n)).unwrap();
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 5,
+                period: 3
+            }
+        );
+
+        assert_eq!(cycle.reduce(4), (4, 0));
+        assert_eq!(cycle.reduce(8), (5, 1));
+        assert_eq!(cycle.reduce(1_000_000_000_000), (7, 333_333_333_331));
+
+        assert_eq!(find_cycle(&mut 3, 7, collatz, |n| Some(*n)), None);
+        assert_eq!(find_cycle(&mut 0, 100, |n| *n += 1, |n| Some(*n)), None);
+        assert_eq!(
+            find_cycle(&mut 3, 100, collatz, |n| (*n < 10).then_some(*n)),
+            None
+        );
+    }
+}
@@ -1,16 +1,53 @@
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let mut sorted: Vec<_> = lines
-        .split(String::is_empty)
-        .map(|i| i.iter().map(|l| l.parse::<i32>().unwrap()).sum::<i32>())
-        .collect();
-    sorted.sort();
-    sorted.reverse();
+use crate::{
+    error::{parse_lines, Error, Result},
+    solution::Solution,
+};
 
-    let part1: i32 = sorted.iter().take(1).sum();
+pub struct Day1;
 
-    println!("Part 1: {}", part1);
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+    type Parsed = Vec<i32>;
+    type Part1 = i32;
+    type Part2 = i32;
 
-    let part2: i32 = sorted.iter().take(3).sum();
-    println!("Part 2: {}", part2);
-    Ok(())
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        let calories = parse_lines(&lines, |l| {
+            if l.is_empty() {
+                Ok(None)
+            } else {
+                l.parse().map(Some).map_err(Error::from)
+            }
+        })?;
+        let mut sorted: Vec<i32> = calories
+            .split(Option::is_none)
+            .map(|i| i.iter().flatten().sum())
+            .collect();
+        sorted.sort();
+        sorted.reverse();
+
+        Ok(sorted)
+    }
+
+    fn part1(&self, sorted: &Self::Parsed) -> Result<i32> {
+        Ok(sorted.iter().take(1).sum())
+    }
+
+    fn part2(&self, sorted: &Self::Parsed) -> Result<i32> {
+        Ok(sorted.iter().take(3).sum())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Day1;
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day1, include_str!("examples/day1.txt")),
+            ("24000".to_string(), "45000".to_string())
+        );
+    }
 }
@@ -1,79 +1,98 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, fmt::Display, str::FromStr};
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let parsed = lines
-        .iter()
-        .map(|l| Instruction::from_str(l))
-        .collect::<Result<Vec<_>, _>>()?;
+use crate::{
+    error::{parse_lines, Error, Result},
+    solution::Solution,
+};
 
-    part1(&parsed);
-    part2(&parsed);
+pub struct Day10;
 
-    Ok(())
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    type Parsed = Vec<Instruction>;
+    type Part1 = i32;
+    type Part2 = Screen;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        parse_lines(&lines, Instruction::from_str)
+    }
+
+    fn part1(&self, parsed: &Self::Parsed) -> Result<i32> {
+        let magic_cycles = HashSet::<i32>::from_iter(vec![20, 60, 100, 140, 180, 220]);
+        let mut strength = 0;
+
+        computer(parsed, |cycle, x| {
+            if magic_cycles.contains(&cycle) {
+                strength += cycle * x;
+            }
+        });
+
+        Ok(strength)
+    }
+
+    fn part2(&self, parsed: &Self::Parsed) -> Result<Screen> {
+        let mut pixels = vec![false; 240];
+        computer(parsed, |cycle, x| {
+            let pos = (cycle - 1) % 40;
+            if pos >= (x - 1) && pos <= (x + 1) {
+                if let Some(p) = pixels.get_mut((cycle - 1) as usize) {
+                    *p = true;
+                }
+            }
+        });
+
+        Ok(Screen(pixels))
+    }
 }
 
 #[derive(Debug)]
-enum Instruction {
+pub enum Instruction {
     Noop,
     Addx(i32),
 }
 
-impl Instruction {
-    fn from_str(str: &str) -> Result<Self, String> {
+impl FromStr for Instruction {
+    type Err = Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
         if let Some(s) = str.strip_prefix("addx ") {
-            let x = s.parse::<i32>().map_err(|e| e.to_string())?;
+            let x = s.parse::<i32>()?;
             Ok(Instruction::Addx(x))
-        } else {
+        } else if str == "noop" {
             Ok(Instruction::Noop)
+        } else {
+            Err(Error::parse("unknown instruction"))
         }
     }
 }
 
-fn part1(parsed: &Vec<Instruction>) {
-    let magic_cycles = HashSet::<i32>::from_iter(vec![20, 60, 100, 140, 180, 220]);
-    let mut strength = 0;
-
-    computer(parsed, |cycle, x| {
-        if magic_cycles.contains(&cycle) {
-            strength += cycle * x;
-        }
-    });
+pub struct Screen(Vec<bool>);
 
-    println!("Part 1 {}", strength)
-}
+impl Display for Screen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .0
+            .chunks(40)
+            .map(|l| {
+                l.iter()
+                    .map(|b| if *b { "#" } else { " " })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
 
-fn part2(parsed: &Vec<Instruction>) {
-    let mut pixels = vec![false; 240];
-    computer(parsed, |cycle, x| {
-        let pos = (cycle - 1) % 40;
-        if pos >= (x - 1) && pos <= (x + 1) {
-            if let Some(p) = pixels.get_mut((cycle - 1) as usize) {
-                *p = true;
-            }
-        }
-    });
-
-    println!();
-    println!("Part 2");
-    pixels.chunks(40).for_each(|l| {
-        let line = l
-            .iter()
-            .map(|b| if *b { "#" } else { " " })
-            .collect::<String>();
-        println!("{}", line)
-    });
+        write!(f, "{}", lines.join("\n"))
+    }
 }
 
-fn computer<F>(parsed: &Vec<Instruction>, mut fun: F)
+fn computer<F>(parsed: &[Instruction], mut fun: F)
 where
     F: FnMut(i32, i32),
 {
     let mut cycle = 1;
-    let mut pointer = 0;
     let mut x = 1;
-    loop {
+    for instruction in parsed {
         fun(cycle, x);
-        match parsed.get(pointer).expect("pointer should stay in range") {
+        match instruction {
             Instruction::Noop => (),
             Instruction::Addx(add) => {
                 cycle += 1;
@@ -82,9 +101,29 @@ where
             }
         }
         cycle += 1;
-        pointer += 1;
-        if pointer >= parsed.len() {
-            break;
-        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Day10;
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        let (part1, part2) = example(Day10, include_str!("examples/day10.txt"));
+
+        assert_eq!(part1, "13140");
+        assert_eq!(
+            part2.lines().collect::<Vec<_>>(),
+            [
+                "##  ##  ##  ##  ##  ##  ##  ##  ##  ##  ",
+                "###   ###   ###   ###   ###   ###   ### ",
+                "####    ####    ####    ####    ####    ",
+                "#####     #####     #####     #####     ",
+                "######      ######      ######      ####",
+                "#######       #######       #######     ",
+            ]
+        );
     }
 }
@@ -1,15 +1,55 @@
 use std::mem;
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    part(1, &lines, 20, true)?;
-    part(2, &lines, 10000, false)
+use crate::{
+    error::{Error, Result},
+    scanner::Scanner,
+    solution::Solution,
+};
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    type Parsed = Vec<Monkey>;
+    type Part1 = i64;
+    type Part2 = i64;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        let mut offset = 0;
+        let monkeys = lines
+            .split(|l| l.is_empty())
+            .map(|strs| {
+                let monkey = Monkey::from_strs(strs).map_err(|e| e.offset_line(offset));
+                offset += strs.len() + 1;
+                monkey
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (i, monkey) in monkeys.iter().enumerate() {
+            for target in [monkey.if_true, monkey.if_false] {
+                if target >= monkeys.len() {
+                    return Err(Error::invalid_value(format!(
+                        "monkey {} throws to missing monkey {}",
+                        i, target
+                    )));
+                }
+            }
+        }
+
+        Ok(monkeys)
+    }
+
+    fn part1(&self, monkeys: &Self::Parsed) -> Result<i64> {
+        monkey_business(monkeys, 20, true)
+    }
+
+    fn part2(&self, monkeys: &Self::Parsed) -> Result<i64> {
+        monkey_business(monkeys, 10000, false)
+    }
 }
 
-fn part(n: i32, lines: &[String], iterations: i32, reduce_worry: bool) -> Result<(), String> {
-    let mut monkeys = lines
-        .split(|l| l.is_empty())
-        .map(Monkey::from_strs)
-        .collect::<Result<Vec<_>, _>>()?;
+fn monkey_business(monkeys: &[Monkey], iterations: i32, reduce_worry: bool) -> Result<i64> {
+    let mut monkeys = monkeys.to_vec();
 
     let mut all_rounds = vec![0; monkeys.len()];
     for _ in 0..iterations {
@@ -24,14 +64,13 @@ fn part(n: i32, lines: &[String], iterations: i32, reduce_worry: bool) -> Result
     all_rounds.sort();
     all_rounds.reverse();
 
-    let monkey_business =
-        (*all_rounds.first().unwrap() as i64) * (*all_rounds.get(1).unwrap() as i64);
-
-    println!("Part {} {}", n, monkey_business);
-    Ok(())
+    match all_rounds[..] {
+        [first, second, ..] => Ok((first as i64) * (second as i64)),
+        _ => Err(Error::missing_section("second monkey")),
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Operation {
     Add(i32),
     Mult(i32),
@@ -48,8 +87,8 @@ impl Operation {
     }
 }
 
-#[derive(Debug)]
-struct Monkey {
+#[derive(Debug, Clone)]
+pub struct Monkey {
     items: Vec<i32>,
     operation: Operation,
     divisibility: i32,
@@ -58,46 +97,55 @@ struct Monkey {
 }
 
 impl Monkey {
-    fn from_strs(strs: &[String]) -> Result<Self, String> {
+    pub fn from_strs(strs: &[String]) -> Result<Self> {
         if strs.len() != 6 {
-            return Err(String::from("Wrong input size"));
+            return Err(Error::parse("expected six lines per monkey")
+                .at_line(0, strs.first().map_or("", |s| s.as_str())));
         }
 
-        let item_start = "  Starting items: ".len();
-        let items = strs[1][item_start..]
-            .split(", ")
-            .map(|s| s.parse::<i32>().map_err(|e| e.to_string()))
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let operation_start = "  Operation: new = old ".len();
-        let operation_rest = &strs[2][operation_start..];
-        let operation = if operation_rest.starts_with('*') {
-            if let Ok(i) = operation_rest[2..].parse::<i32>() {
-                Operation::Mult(i)
+        Scanner::parse_line(strs, 0, |s| {
+            s.literal("Monkey ")?;
+            s.integer::<usize>()?;
+            s.literal(":")
+        })?;
+
+        let items = Scanner::parse_line(strs, 1, |s| {
+            s.literal("  Starting items: ")?;
+            s.separated(", ", Scanner::integer)
+        })?;
+
+        let operation = Scanner::parse_line(strs, 2, |s| {
+            s.literal("  Operation: new = old ")?;
+            if s.eat("* old") {
+                Ok(Operation::Square)
+            } else if s.eat("* ") {
+                Ok(Operation::Mult(s.integer()?))
+            } else if s.eat("+ ") {
+                Ok(Operation::Add(s.integer()?))
             } else {
-                Operation::Square
+                Err(s.error(Error::parse("expected \"* \" or \"+ \"")))
             }
-        } else {
-            let i = operation_rest[2..]
-                .parse::<i32>()
-                .map_err(|e| e.to_string())?;
-            Operation::Add(i)
-        };
-
-        let test_start = "  Test: divisible by ".len();
-        let divisibility = strs[3][test_start..]
-            .parse::<i32>()
-            .map_err(|e| e.to_string())?;
-
-        let if_true_start = "    If true: throw to monkey ".len();
-        let if_true = strs[4][if_true_start..]
-            .parse::<usize>()
-            .map_err(|e| e.to_string())?;
-
-        let if_false_start = "    If false: throw to monkey ".len();
-        let if_false = strs[5][if_false_start..]
-            .parse::<usize>()
-            .map_err(|e| e.to_string())?;
+        })?;
+
+        let divisibility = Scanner::parse_line(strs, 3, |s| {
+            s.literal("  Test: divisible by ")?;
+            let start = s.clone();
+            let divisibility = s.integer()?;
+            if divisibility <= 0 {
+                return Err(start.error(Error::invalid_value("divisor must be positive")));
+            }
+            Ok(divisibility)
+        })?;
+
+        let if_true = Scanner::parse_line(strs, 4, |s| {
+            s.literal("    If true: throw to monkey ")?;
+            s.integer()
+        })?;
+
+        let if_false = Scanner::parse_line(strs, 5, |s| {
+            s.literal("    If false: throw to monkey ")?;
+            s.integer()
+        })?;
 
         Ok(Monkey {
             items,
@@ -144,3 +192,17 @@ fn round(monkeys: &mut [Monkey], reduce_worry: bool) -> RoundStats {
 
     RoundStats { inspections }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Day11;
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day11, include_str!("examples/day11.txt")),
+            ("10605".to_string(), "2713310158".to_string())
+        );
+    }
+}
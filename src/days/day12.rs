@@ -1,50 +1,60 @@
-use std::collections::{HashMap, HashSet, VecDeque};
-
-use super::point::Point2d;
-
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let grid = Grid::from_strs(&lines);
+use super::{
+    grid::Grid,
+    point::Point2d,
+    search::{Graph, Search},
+};
+use crate::{
+    error::{Error, Result},
+    solution::Solution,
+};
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    type Parsed = Heightmap;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        Heightmap::from_strs(&lines)
+    }
 
-    println!(
-        "Part 1 {}",
+    fn part1(&self, grid: &Self::Parsed) -> Result<u32> {
         distance(
-            &grid.start(),
-            &grid,
+            &grid.start()?,
+            grid,
             |e| e.is_highest(),
-            |c, n| n.can_traverse(c)
+            |c, n| n.can_traverse(c),
         )
-        .unwrap_or_default()
-    );
+        .ok_or_else(|| Error::unsolvable("no path to the best signal"))
+    }
 
-    println!(
-        "Part 2 {}",
+    fn part2(&self, grid: &Self::Parsed) -> Result<u32> {
         distance(
-            &grid.end(),
-            &grid,
+            &grid.end()?,
+            grid,
             |e| e.height() == 1,
-            |c, n| c.can_traverse(n)
+            |c, n| c.can_traverse(n),
         )
-        .unwrap_or_default()
-    );
-
-    Ok(())
+        .ok_or_else(|| Error::unsolvable("no path from the lowest elevation"))
+    }
 }
 
 #[derive(PartialEq, Debug)]
-enum Elevation {
+pub enum Elevation {
     Lowest,
     Highest,
     Level(char),
 }
 
 impl Elevation {
-    fn from_char(c: char) -> Self {
-        if c == 'E' {
-            Elevation::Highest
-        } else if c == 'S' {
-            Elevation::Lowest
-        } else {
-            Elevation::Level(c)
+    fn from_char(c: char) -> Result<Self> {
+        match c {
+            'E' => Ok(Elevation::Highest),
+            'S' => Ok(Elevation::Lowest),
+            'a'..='z' => Ok(Elevation::Level(c)),
+            _ => Err(Error::invalid_value(format!("unknown elevation {:?}", c))),
         }
     }
 
@@ -65,79 +75,78 @@ impl Elevation {
     }
 }
 
-fn distance<F1, F2>(start: &Point2d, grid: &Grid, target: F1, can_traverse: F2) -> Option<u32>
+/// The steps allowed across a heightmap, one cell at a time.
+struct Route<'a, F> {
+    grid: &'a Heightmap,
+    can_traverse: F,
+}
+
+impl<'a, F> Graph for Route<'a, F>
+where
+    F: Fn(&Elevation, &Elevation) -> bool,
+{
+    type Node = Point2d;
+
+    fn neighbours(&self, point: &Point2d) -> Vec<(Point2d, u32)> {
+        let current = self.grid.0.get(point).unwrap();
+        self.grid
+            .0
+            .neighbours(point)
+            .filter(|(_, e)| (self.can_traverse)(current, e))
+            .map(|(n, _)| (n, 1))
+            .collect()
+    }
+}
+
+fn distance<F1, F2>(start: &Point2d, grid: &Heightmap, target: F1, can_traverse: F2) -> Option<u32>
 where
     F1: Fn(&Elevation) -> bool,
     F2: Fn(&Elevation, &Elevation) -> bool,
 {
-    let mut visited = HashSet::new();
-    let mut positions = VecDeque::new();
-    positions.push_back(PositionDistance(0, start.clone()));
-    visited.insert(start.clone());
-
-    loop {
-        if let Some(next) = positions.pop_front() {
-            let point = &next.1;
-            let neighbours = vec![point.left(), point.right(), point.up(), point.down()];
-            let current = grid.0.get(point).unwrap();
-            for n in neighbours {
-                if !visited.contains(&n) {
-                    if let Some(e) = grid.0.get(&n) {
-                        if can_traverse(current, e) {
-                            if target(e) {
-                                return Some(next.0 + 1);
-                            }
-                            positions.push_back(PositionDistance(1 + next.0, n.clone()));
-                            visited.insert(n.clone());
-                        }
-                    }
-                }
-            }
-        } else {
-            return None;
-        }
-    }
+    let route = Route { grid, can_traverse };
+    Search::new()
+        .bfs(&route, start.clone(), |p| {
+            grid.0.get(p).is_some_and(&target)
+        })
+        .map(|found| found.cost)
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
-struct PositionDistance(u32, Point2d);
-
-struct Grid(HashMap<Point2d, Elevation>);
-
-impl Grid {
-    fn from_strs(strs: &[String]) -> Self {
-        let mut map = HashMap::new();
-        strs.iter()
-            .enumerate()
-            .flat_map(|(y, s)| {
-                s.chars()
-                    .enumerate()
-                    .map(move |(x, c)| (x, y, Elevation::from_char(c)))
-            })
-            .for_each(|(x, y, e)| {
-                map.insert(
-                    Point2d::new(x.try_into().unwrap(), y.try_into().unwrap()),
-                    e,
-                );
-            });
-        Grid(map)
+pub struct Heightmap(Grid<Elevation>);
+
+impl Heightmap {
+    pub fn from_strs(strs: &[String]) -> Result<Self> {
+        Ok(Heightmap(Grid::parse(strs, |c| {
+            Elevation::from_char(c).map(Some)
+        })?))
     }
 
-    fn start(&self) -> Point2d {
+    fn find(&self, elevation: Elevation, name: &str) -> Result<Point2d> {
         self.0
             .iter()
-            .find(|(_, e)| **e == Elevation::Lowest)
-            .unwrap()
-            .0
-            .clone()
+            .find(|(_, e)| **e == elevation)
+            .map(|(p, _)| p)
+            .ok_or_else(|| Error::missing_section(name))
     }
 
-    fn end(&self) -> Point2d {
-        self.0
-            .iter()
-            .find(|(_, e)| **e == Elevation::Highest)
-            .unwrap()
-            .0
-            .clone()
+    fn start(&self) -> Result<Point2d> {
+        self.find(Elevation::Lowest, "start position S")
+    }
+
+    fn end(&self) -> Result<Point2d> {
+        self.find(Elevation::Highest, "best signal position E")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Day12;
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day12, include_str!("examples/day12.txt")),
+            ("31".to_string(), "29".to_string())
+        );
     }
 }
@@ -1,86 +1,116 @@
-use std::{cmp::Ordering, iter::Peekable, str::Chars};
-
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let parsed_lines: Vec<_> = lines
-        .iter()
-        .map(|l| {
-            if l.is_empty() {
-                None
-            } else {
-                Some(List::from_str(l))
-            }
-        })
-        .collect();
-
-    let parsed_pairs: Vec<_> = parsed_lines
-        .split(|l| l.is_none())
-        .map(|i| Pair(i[0].clone().unwrap(), i[1].clone().unwrap()))
-        .collect();
-
-    let part1 = parsed_pairs
-        .iter()
-        .enumerate()
-        .filter(|(_, p)| p.compare())
-        .map(|(i, _)| i + 1)
-        .sum::<usize>();
-
-    println!("Part 1 {}", part1);
-
-    let mut packets: Vec<_> = parsed_lines.iter().filter_map(|p| p.as_ref()).collect();
-    let m1 = List::from_str("[[2]]");
-    let m2 = List::from_str("[[6]]");
-    packets.push(&m1);
-    packets.push(&m2);
-    packets.sort();
-
-    let part2 = packets
-        .into_iter()
-        .enumerate()
-        .filter(|(_, v)| **v == m1 || **v == m2)
-        .map(|(i, _)| i + 1)
-        .product::<usize>();
-
-    println!("Part 2 {}", part2);
-
-    Ok(())
+use std::{
+    cmp::Ordering,
+    iter::Peekable,
+    str::{Chars, FromStr},
+};
+
+use crate::{
+    error::{Error, Result},
+    solution::Solution,
+};
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+    type Parsed = Vec<Pair>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        let mut offset = 0;
+        lines
+            .split(|l| l.is_empty())
+            .map(|i| {
+                let pair = match i {
+                    [left, right] => Ok(Pair(
+                        left.parse().map_err(|e: Error| e.at_line(offset, left))?,
+                        right
+                            .parse()
+                            .map_err(|e: Error| e.at_line(offset + 1, right))?,
+                    )),
+                    _ => Err(Error::parse("expected pairs of packets")
+                        .at_line(offset, i.first().map_or("", |s| s.as_str()))),
+                };
+                offset += i.len() + 1;
+                pair
+            })
+            .collect()
+    }
+
+    fn part1(&self, parsed_pairs: &Self::Parsed) -> Result<usize> {
+        Ok(parsed_pairs
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.compare())
+            .map(|(i, _)| i + 1)
+            .sum::<usize>())
+    }
+
+    fn part2(&self, parsed_pairs: &Self::Parsed) -> Result<usize> {
+        let mut packets: Vec<_> = parsed_pairs.iter().flat_map(|Pair(l, r)| [l, r]).collect();
+        let m1 = List::from_str("[[2]]")?;
+        let m2 = List::from_str("[[6]]")?;
+        packets.push(&m1);
+        packets.push(&m2);
+        packets.sort();
+
+        Ok(packets
+            .into_iter()
+            .enumerate()
+            .filter(|(_, v)| **v == m1 || **v == m2)
+            .map(|(i, _)| i + 1)
+            .product::<usize>())
+    }
 }
 
 #[derive(Debug, Clone, Eq)]
-enum List {
+pub enum List {
     Nested(Vec<List>),
     Value(u8),
 }
 
-impl List {
-    fn from_str(str: &str) -> Self {
-        List::from_chars(&mut str.chars().peekable())
+impl FromStr for List {
+    type Err = Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let mut chars = str.chars().peekable();
+        let list = List::from_chars(&mut chars)?;
+        match chars.next() {
+            None => Ok(list),
+            Some(c) => Err(Error::parse(format!("unexpected {:?} after packet", c))),
+        }
     }
+}
+
+impl List {
+    fn from_chars(chars: &mut Peekable<Chars>) -> Result<Self> {
+        let missing_close = || Error::parse("missing closing bracket");
 
-    fn from_chars(chars: &mut Peekable<Chars>) -> Self {
         let mut nested = Vec::new();
         if let Some('[') = chars.next() {
             'parse_nested: loop {
                 if let Some('[') = chars.peek() {
-                    let n = List::from_chars(chars);
+                    let n = List::from_chars(chars)?;
                     nested.push(n);
 
-                    let a = chars.next().expect("missing closing brace");
+                    let a = chars.next().ok_or_else(missing_close)?;
                     if a == ']' {
                         break 'parse_nested;
                     }
                 } else {
-                    let a = chars.next().expect("missing closing brace");
+                    let a = chars.next().ok_or_else(missing_close)?;
                     if a == ']' {
                         break 'parse_nested;
                     }
 
                     let mut n = vec![a];
                     'parse_number: loop {
-                        let a = chars.next().expect("missing closing brace");
+                        let a = chars.next().ok_or_else(missing_close)?;
                         if a == ',' {
                             break 'parse_number;
                         } else if a == ']' {
-                            let i = n.iter().collect::<String>().parse::<u8>().expect("number");
+                            let i = n.iter().collect::<String>().parse::<u8>()?;
                             let v = List::Value(i);
                             nested.push(v);
                             break 'parse_nested;
@@ -88,14 +118,14 @@ impl List {
                             n.push(a);
                         }
                     }
-                    let i = n.iter().collect::<String>().parse::<u8>().expect("number");
+                    let i = n.iter().collect::<String>().parse::<u8>()?;
                     let v = List::Value(i);
                     nested.push(v);
                 }
             }
-            List::Nested(nested)
+            Ok(List::Nested(nested))
         } else {
-            panic!("missing opening brace");
+            Err(Error::parse("missing opening bracket"))
         }
     }
 }
@@ -108,26 +138,104 @@ impl PartialEq for List {
 
 impl PartialOrd for List {
     fn partial_cmp(&self, right: &Self) -> Option<std::cmp::Ordering> {
-        Some(match (self, right) {
-            (List::Value(l), List::Value(r)) => l.cmp(r),
-            (List::Nested(l), List::Nested(r)) => l.cmp(r),
-            (l @ List::Nested(_), r @ List::Value(_)) => l.cmp(&List::Nested(vec![r.clone()])),
-            (l @ List::Value(_), r @ List::Nested(_)) => List::Nested(vec![l.clone()]).cmp(r),
-        })
+        Some(self.cmp(right))
     }
 }
 
 impl Ord for List {
     fn cmp(&self, right: &Self) -> Ordering {
-        self.partial_cmp(right).unwrap()
+        match (self, right) {
+            (List::Value(l), List::Value(r)) => l.cmp(r),
+            (List::Nested(l), List::Nested(r)) => l.cmp(r),
+            (l @ List::Nested(_), r @ List::Value(_)) => l.cmp(&List::Nested(vec![r.clone()])),
+            (l @ List::Value(_), r @ List::Nested(_)) => List::Nested(vec![l.clone()]).cmp(r),
+        }
     }
 }
 
 #[derive(Debug)]
-struct Pair(List, List);
+pub struct Pair(List, List);
 
 impl Pair {
     fn compare(&self) -> bool {
         self.0 <= self.1
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+
+    use proptest::prelude::*;
+
+    use super::{Day13, List};
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day13, include_str!("examples/day13.txt")),
+            ("13".to_string(), "140".to_string())
+        );
+    }
+
+    #[test]
+    fn list_order() {
+        let list = |s: &str| s.parse::<List>().unwrap();
+
+        assert!(list("[1,1,3,1,1]") < list("[1,1,5,1,1]"));
+        assert!(list("[[1],[2,3,4]]") < list("[[1],4]"));
+        assert!(list("[9]") > list("[[8,7,6]]"));
+        assert!(list("[[4,4],4,4]") < list("[[4,4],4,4,4]"));
+        assert!(list("[7,7,7,7]") > list("[7,7,7]"));
+        assert!(list("[]") < list("[3]"));
+        assert!(list("[[[]]]") > list("[[]]"));
+        assert!(list("[1,[2,[3,[4,[5,6,7]]]],8,9]") > list("[1,[2,[3,[4,[5,6,0]]]],8,9]"));
+        assert!(list("[10]") > list("[9]"));
+
+        assert_eq!(list("[1]"), list("[[1]]"));
+        assert_eq!(list("[[2]]"), list("[2]"));
+        assert_ne!(list("[]"), list("[[]]"));
+
+        assert!("[1,2".parse::<List>().is_err());
+        assert!("[1]]".parse::<List>().is_err());
+    }
+
+    /// Small packets over a handful of values so that ties are common.
+    fn list() -> impl Strategy<Value = List> {
+        (0u8..4)
+            .prop_map(List::Value)
+            .prop_recursive(4, 24, 4, |inner| {
+                prop::collection::vec(inner, 0..4).prop_map(List::Nested)
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn list_order_is_antisymmetric(a in list(), b in list()) {
+            prop_assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+            prop_assert_eq!(a.cmp(&b) == Ordering::Equal, a == b);
+            prop_assert_eq!(a.cmp(&a), Ordering::Equal);
+        }
+
+        #[test]
+        fn list_order_is_transitive(a in list(), b in list(), c in list()) {
+            if a <= b && b <= c {
+                prop_assert!(a <= c);
+            }
+            if a < b && b < c {
+                prop_assert!(a < c);
+            }
+        }
+
+        #[test]
+        fn sorted_lists_are_ordered(mut lists in prop::collection::vec(list(), 0..12)) {
+            lists.sort();
+            for (i, a) in lists.iter().enumerate() {
+                for b in &lists[i..] {
+                    prop_assert!(a <= b);
+                }
+            }
+        }
+    }
+}
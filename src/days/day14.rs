@@ -1,68 +1,113 @@
-use std::collections::HashMap;
+use std::fmt::Display;
 
-use super::point::Point2d;
+use super::{grid::Grid, point::Point2d};
+use crate::{
+    error::{Error, Result},
+    solution::Solution,
+    visualize::Frame,
+};
 
-const PRINT: bool = false;
+pub struct Day14;
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let mut caves = Caves::from_strs(&lines);
+impl Solution for Day14 {
+    const DAY: u8 = 14;
+    type Parsed = Caves;
+    type Part1 = i32;
+    type Part2 = i32;
 
-    if PRINT {
-        caves.print();
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        Caves::from_strs(&lines)
     }
 
-    let mut sand = 0;
-    while caves.drop_sand() {
-        sand += 1;
+    fn part1(&self, caves: &Self::Parsed) -> Result<i32> {
+        let mut caves = caves.clone();
+        Ok(fill(&mut caves))
     }
 
-    println!("Part 1 {}", sand);
+    fn part2(&self, caves: &Self::Parsed) -> Result<i32> {
+        let mut caves = caves.clone();
 
-    if PRINT {
-        caves.print();
+        let mut sand = fill(&mut caves);
+        caves.with_floor();
+        sand += fill(&mut caves);
+        Ok(sand)
     }
 
-    caves.with_floor();
-    while caves.drop_sand() {
-        sand += 1;
-    }
+    /// Pours sand until it falls into the abyss, then onto the floor until
+    /// the source is blocked, as the two parts do.
+    fn visualize(&self, caves: &Self::Parsed, show: &mut dyn FnMut(Frame)) -> Result<bool> {
+        let mut caves = caves.clone();
+        show(caves.frame());
 
-    println!("Part 2 {}", sand);
+        for floor in [false, true] {
+            if floor {
+                caves.with_floor();
+            }
+            while let Some(grain) = caves.drop_sand() {
+                show(caves.frame().focused_on(grain));
+            }
+        }
 
-    if PRINT {
-        caves.print();
+        Ok(true)
     }
+}
 
-    Ok(())
+fn fill(caves: &mut Caves) -> i32 {
+    let mut sand = 0;
+    while caves.drop_sand().is_some() {
+        sand += 1;
+    }
+    sand
 }
 
-#[derive(Debug, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone)]
 enum Occupied {
     Rock,
     Sand,
 }
 
-struct Caves(HashMap<Point2d, Occupied>, i32, bool);
+impl Display for Occupied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Occupied::Rock => write!(f, "#"),
+            Occupied::Sand => write!(f, "o"),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Caves(Grid<Occupied>, i32, bool);
 
 impl Caves {
-    fn from_strs(strs: &[String]) -> Self {
-        let mut map = HashMap::new();
+    pub fn from_strs(strs: &[String]) -> Result<Self> {
+        let mut map = Grid::new();
 
-        for str in strs {
-            let coords: Vec<_> = str
+        for (i, str) in strs.iter().enumerate() {
+            let coords = str
                 .split(" -> ")
                 .map(|s| {
-                    let split: Vec<_> = s.split(',').collect();
-                    let x = split[0].parse::<i32>().unwrap();
-                    let y = split[1].parse::<i32>().unwrap();
+                    let (x, y) = s
+                        .split_once(',')
+                        .ok_or_else(|| Error::parse("expected \"<x>,<y>\""))?;
 
-                    Point2d::new(x, y)
+                    Ok(Point2d::new(x.parse()?, y.parse()?))
                 })
-                .collect();
+                .collect::<Result<Vec<_>>>()
+                .map_err(|e| e.at_line(i, str))?;
 
-            coords.windows(2).for_each(|c| {
+            for c in coords.windows(2) {
                 let p1 = &c[0];
                 let p2 = &c[1];
+                if p1.x() != p2.x() && p1.y() != p2.y() {
+                    return Err(Error::invalid_value(format!(
+                        "rock line {},{} -> {},{} is diagonal",
+                        p1.x(),
+                        p1.y(),
+                        p2.x(),
+                        p2.y()
+                    ))
+                    .at_line(i, str));
+                }
 
                 let do_move = |p: &Point2d| {
                     if p1.x() < p2.x() {
@@ -86,11 +131,15 @@ impl Caves {
                     }
                     p = do_move(&p)
                 }
-            });
+            }
         }
 
-        let min_y = map.keys().max_by_key(|p| p.y()).unwrap().y();
-        Caves(map, min_y, false)
+        let min_y = map
+            .bounds()
+            .ok_or_else(|| Error::missing_section("rock paths"))?
+            .max
+            .y();
+        Ok(Caves(map, min_y, false))
     }
 
     fn bottom(&self) -> i32 {
@@ -101,11 +150,13 @@ impl Caves {
         self.2 = true
     }
 
-    fn drop_sand(&mut self) -> bool {
+    /// Where the next grain of sand comes to rest, or `None` if it falls
+    /// into the abyss or the source is blocked.
+    fn drop_sand(&mut self) -> Option<Point2d> {
         let start = Point2d::new(500, 0);
 
-        if self.0.contains_key(&start) {
-            return false;
+        if self.0.contains(&start) {
+            return None;
         }
 
         let mut position = start;
@@ -122,7 +173,7 @@ impl Caves {
 
             cont = false;
             for attempt in attempts {
-                if !self.0.contains_key(&attempt) {
+                if !self.0.contains(&attempt) {
                     position = attempt.clone();
                     cont = true;
                     break;
@@ -133,33 +184,72 @@ impl Caves {
         let settled = !cont || has_floor;
 
         if settled {
-            self.0.insert(position, Occupied::Sand);
+            self.0.insert(position.clone(), Occupied::Sand);
         }
 
-        settled
+        settled.then_some(position)
     }
 
-    fn print(&self) {
-        let map = &self.0;
-        let min_x = map.keys().min_by_key(|p| p.x()).unwrap().x();
-        let min_y = map.keys().min_by_key(|p| p.y()).unwrap().y();
-
-        let max_x = map.keys().max_by_key(|p| p.x()).unwrap().x();
-        let max_y = map.keys().max_by_key(|p| p.y()).unwrap().y();
+    /// The rock and sand, with the source and the floor if there is one.
+    fn frame(&self) -> Frame {
+        let mut grid = Grid::new();
+        for (p, occupied) in self.0.iter() {
+            let c = match occupied {
+                Occupied::Rock => '#',
+                Occupied::Sand => 'o',
+            };
+            grid.insert(p, c);
+        }
+        let source = Point2d::new(500, 0);
+        if !grid.contains(&source) {
+            grid.insert(source, '+');
+        }
 
-        for y in min_y..max_y + 1 {
-            for x in min_x..max_x + 1 {
-                if let Some(p) = map.get(&Point2d::new(x, y)) {
-                    if matches!(p, Occupied::Rock) {
-                        print!("#");
-                    } else {
-                        print!("o");
-                    }
-                } else {
-                    print!(" ");
+        if self.2 {
+            if let Some(bounds) = grid.bounds() {
+                let floor = self.bottom() + 2;
+                for x in bounds.min.x() - 1..=bounds.max.x() + 1 {
+                    grid.insert(Point2d::new(x, floor), '#');
                 }
             }
-            println!()
         }
+
+        Frame::new(grid)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Day14;
+    use crate::{
+        days::point::Point2d,
+        solution::{example, Solution},
+        visualize::Frame,
+    };
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day14, include_str!("examples/day14.txt")),
+            ("24".to_string(), "93".to_string())
+        );
+    }
+
+    #[test]
+    fn visualize() {
+        let lines = include_str!("examples/day14.txt")
+            .lines()
+            .map(String::from)
+            .collect();
+        let caves = Day14.parse(lines).unwrap();
+        let mut frames: Vec<Frame> = Vec::new();
+
+        assert!(Day14.visualize(&caves, &mut |f| frames.push(f)).unwrap());
+        assert_eq!(frames.len(), 1 + 93);
+        assert_eq!(frames[0].grid.get(&Point2d::new(500, 0)), Some(&'+'));
+
+        let last = frames.last().unwrap();
+        assert_eq!(last.focus, Some(Point2d::new(500, 0)));
+        assert_eq!(last.grid.get(&Point2d::new(500, 11)), Some(&'#'));
     }
 }
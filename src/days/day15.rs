@@ -1,219 +1,341 @@
-use super::point::Point2d;
+use std::{fmt::Display, str::FromStr};
 
-const Y_CHECK: i32 = 2000000;
+use super::{interval::IntervalSet, point::Point2d};
+use crate::{
+    error::{parse_lines, Error, Result},
+    params::Params,
+    scanner::Scanner,
+    solution::Solution,
+};
 
-const MIN_SEARCH: i32 = 0;
-const MAX_SEARCH: i32 = 4000000;
+const ROW: i32 = 2000000;
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let parsed = lines
-        .iter()
-        .map(|l| Sensor::from_str(l))
-        .collect::<Result<Vec<_>, _>>()?;
-
-    let mut ranges = Ranges::new();
-
-    parsed
-        .iter()
-        .filter_map(|p| p.x_range(Y_CHECK))
-        .for_each(|p| ranges.add(p));
-
-    let mut beacons_at_check: Vec<_> = parsed
-        .iter()
-        .map(|s| s.closest_beacon.clone())
-        .filter(|p| p.y() == Y_CHECK)
-        .collect();
-
-    beacons_at_check.sort();
-    beacons_at_check.dedup();
+const SEARCH_MIN: i32 = 0;
+const SEARCH_MAX: i32 = 4000000;
 
-    let part1 = ranges.size() - (beacons_at_check.len() as i32);
+const TUNING: i64 = 4000000;
 
-    println!("Part 1 {}", part1);
+pub struct Day15;
 
-    for y in MIN_SEARCH..(MAX_SEARCH + 1) {
-        let mut ranges = Ranges::new();
-
-        parsed
-            .iter()
-            .filter_map(|p| p.x_range(y))
-            .for_each(|p| ranges.add(p));
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+    const PARAMS: &'static [&'static str] = &["row", "search_min", "search_max", "solver"];
+    type Parsed = Survey;
+    type Part1 = u128;
+    type Part2 = i64;
 
-        if let Some(x) = ranges.first_free_space(MIN_SEARCH, MAX_SEARCH) {
-            let tuning_freq: i64 = (x as i64) * 4000000 + (y as i64);
-            println!("Part 2 {}", tuning_freq);
-            break;
-        }
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        self.parse_with(lines, &Params::new())
     }
 
-    Ok(())
-}
-
-#[derive(Debug)]
-struct Sensor {
-    location: Point2d,
-    closest_beacon: Point2d,
-}
-
-impl Sensor {
-    fn from_str(str: &str) -> Result<Self, String> {
-        let split: Vec<_> = str.split(' ').collect();
-        if split.len() != 10 {
-            return Err("Wrong number of words".to_string());
+    /// Takes `row` as the row to count for part 1, `search_min` and
+    /// `search_max` as the range of x and y the distress beacon is in, and
+    /// `solver` as the [`Solver`] for part 2.
+    fn parse_with(&self, lines: Vec<String>, params: &Params) -> Result<Self::Parsed> {
+        let search = (
+            params.get_or("search_min", SEARCH_MIN)?,
+            params.get_or("search_max", SEARCH_MAX)?,
+        );
+        if search.0 > search.1 {
+            return Err(Error::invalid_value(format!(
+                "the search range {}..={} is empty",
+                search.0, search.1
+            )));
         }
 
-        let x_loc_str = split[2];
-        let y_loc_str = split[3];
-        let x_beac_str = split[8];
-        let y_beac_str = split[9];
-
-        let x_loc = x_loc_str
-            .strip_prefix("x=")
-            .and_then(|s| s.strip_suffix(','))
-            .and_then(|s| s.parse::<i32>().ok())
-            .ok_or_else(|| "failed to parse x location".to_string())?;
-        let y_loc = y_loc_str
-            .strip_prefix("y=")
-            .and_then(|s| s.strip_suffix(':'))
-            .and_then(|s| s.parse::<i32>().ok())
-            .ok_or_else(|| "failed to parse y location".to_string())?;
-        let x_beac = x_beac_str
-            .strip_prefix("x=")
-            .and_then(|s| s.strip_suffix(','))
-            .and_then(|s| s.parse::<i32>().ok())
-            .ok_or_else(|| "failed to parse x beacon".to_string())?;
-        let y_beac = y_beac_str
-            .strip_prefix("y=")
-            .and_then(|s| s.parse::<i32>().ok())
-            .ok_or_else(|| "failed to parse y beacon".to_string())?;
-
-        Ok(Sensor {
-            location: Point2d::new(x_loc, y_loc),
-            closest_beacon: Point2d::new(x_beac, y_beac),
+        Ok(Survey {
+            sensors: parse_lines(&lines, Sensor::from_str)?,
+            row: params.get_or("row", ROW)?,
+            search,
+            solver: params.get_or("solver", Solver::Boundaries)?,
         })
     }
 
-    fn beacon_distance(&self) -> u32 {
-        self.location.x().abs_diff(self.closest_beacon.x())
-            + self.location.y().abs_diff(self.closest_beacon.y())
-    }
+    fn part1(&self, survey: &Self::Parsed) -> Result<u128> {
+        let row = survey.row;
+        let covered: IntervalSet<_> = survey
+            .sensors
+            .iter()
+            .filter_map(|p| p.x_range(row))
+            .collect();
 
-    fn x_range(&self, y: i32) -> Option<(i32, i32)> {
-        let distance = self.beacon_distance();
+        let mut beacons_at_check: Vec<_> = survey
+            .sensors
+            .iter()
+            .map(|s| s.closest_beacon.clone())
+            .filter(|p| p.y() == row)
+            .collect();
 
-        if self.location.y().abs_diff(y) > distance {
-            return None;
-        }
+        beacons_at_check.sort();
+        beacons_at_check.dedup();
 
-        let diff: i32 = (distance - self.location.y().abs_diff(y))
-            .try_into()
-            .unwrap();
+        Ok(covered.len() - beacons_at_check.len() as u128)
+    }
 
-        let x_min = self.location.x() - diff;
-        let x_max = self.location.x() + diff;
+    fn part2(&self, survey: &Self::Parsed) -> Result<i64> {
+        let beacon = match survey.solver {
+            Solver::Rows => scan_rows(survey),
+            Solver::Boundaries => intersect_boundaries(survey),
+        };
 
-        Some((x_min, x_max))
+        beacon
+            .map(|p| (p.x() as i64) * TUNING + (p.y() as i64))
+            .ok_or_else(|| Error::unsolvable("no free space found"))
     }
 }
 
-struct Ranges(Vec<(i32, i32)>);
+/// The sensors, and where to look for the distress beacon.
+#[derive(Debug)]
+pub struct Survey {
+    sensors: Vec<Sensor>,
+    row: i32,
+    search: (i32, i32),
+    solver: Solver,
+}
 
-impl Ranges {
-    fn new() -> Self {
-        Ranges(Vec::new())
-    }
+/// Ways of finding the one position no sensor covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Solver {
+    /// Merges what the sensors cover in each row of the search area.
+    Rows,
+    /// Only looks where the edges of the sensors' diamonds meet.
+    Boundaries,
+}
 
-    fn add(&mut self, mut r: (i32, i32)) {
-        let mut merged = false;
-        for p in self.0.iter_mut() {
-            if r.1 >= p.0 && r.0 <= p.1 {
-                r = (p.0.min(r.0), p.1.max(r.1));
-                *p = r;
-                merged = true;
-            }
-        }
+impl FromStr for Solver {
+    type Err = String;
 
-        if !merged {
-            self.0.push(r);
-            self.0.sort()
-        } else {
-            self.0.dedup();
-
-            let biggest = self
-                .0
-                .iter()
-                .copied()
-                .filter(|(x, _)| *x == r.0)
-                .max_by_key(|t| t.1)
-                .unwrap();
-
-            self.0.retain(|p| p.0 != biggest.0 || *p == biggest);
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str {
+            "rows" => Ok(Solver::Rows),
+            "boundaries" => Ok(Solver::Boundaries),
+            _ => Err(format!("unknown solver '{}'", str)),
         }
     }
+}
 
-    fn first_free_space(&self, min_x: i32, max_x: i32) -> Option<i32> {
-        for b in &self.0 {
-            if b.0 > min_x && b.0 <= max_x + 1 {
-                return Some(b.0 - 1);
-            }
+impl Display for Solver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Solver::Rows => write!(f, "rows"),
+            Solver::Boundaries => write!(f, "boundaries"),
         }
-
-        None
-    }
-
-    fn size(&self) -> i32 {
-        self.0.iter().map(|(a, b)| b - a + 1).sum()
     }
 }
 
-#[cfg(test)]
-mod test {
-    use super::Ranges;
+fn scan_rows(survey: &Survey) -> Option<Point2d> {
+    let (min, max) = survey.search;
 
-    #[test]
-    fn range() {
-        let mut ranges = Ranges::new();
+    (min..=max).find_map(|y| {
+        let covered: IntervalSet<_> = survey.sensors.iter().filter_map(|p| p.x_range(y)).collect();
 
-        ranges.add((10, 10));
+        let gap = covered.gaps(min as i64, max as i64).next();
+        gap.map(|(x, _)| Point2d::new(x as i32, y))
+    })
+}
 
-        assert_eq!(ranges.size(), 1);
-        assert_eq!(ranges.first_free_space(0, 10), Some(9));
+/// Turned 45 degrees, to `u = x + y` and `v = x - y`, every sensor covers a
+/// square. A lone uncovered position sits just outside one of those squares,
+/// so on a line `u` or `v` one past its edge. Along that line it is boxed in
+/// by the next square over, one or two steps past that square's edge, or it
+/// is in a corner of the search area. Those are the only candidates.
+fn intersect_boundaries(survey: &Survey) -> Option<Point2d> {
+    let (min, max) = (survey.search.0 as i64, survey.search.1 as i64);
+    let squares: Vec<_> = survey
+        .sensors
+        .iter()
+        .map(|s| {
+            let (x, y) = (s.location.x() as i64, s.location.y() as i64);
+            (x + y, x - y, s.beacon_distance())
+        })
+        .collect();
 
-        ranges.add((0, 0));
+    let edges = |centre: fn(&(i64, i64, i64)) -> i64, steps: &[i64]| -> Vec<i64> {
+        squares
+            .iter()
+            .flat_map(|s| {
+                steps
+                    .iter()
+                    .flat_map(move |d| [centre(s) - s.2 - d, centre(s) + s.2 + d])
+            })
+            .collect()
+    };
+    let (us, vs) = (edges(|s| s.0, &[1]), edges(|s| s.1, &[1]));
+    let (near_us, near_vs) = (edges(|s| s.0, &[1, 2]), edges(|s| s.1, &[1, 2]));
+
+    let along_u = us
+        .iter()
+        .flat_map(|&u| near_vs.iter().map(move |&v| (u, v)));
+    let along_v = vs
+        .iter()
+        .flat_map(|&v| near_us.iter().map(move |&u| (u, v)));
+    let corners = [(min, min), (min, max), (max, min), (max, max)].map(|(x, y)| (x + y, x - y));
+
+    along_u
+        .chain(along_v)
+        .chain(corners)
+        .filter(|(u, v)| (u + v) % 2 == 0)
+        .map(|(u, v)| ((u + v) / 2, (u - v) / 2))
+        .filter(|(x, y)| (min..=max).contains(x) && (min..=max).contains(y))
+        .map(|(x, y)| Point2d::new(x as i32, y as i32))
+        .find(|p| survey.sensors.iter().all(|s| !s.covers(p)))
+}
 
-        assert_eq!(ranges.size(), 2);
-        assert_eq!(ranges.first_free_space(0, 10), Some(9));
+#[derive(Debug)]
+pub struct Sensor {
+    location: Point2d,
+    closest_beacon: Point2d,
+}
+
+impl FromStr for Sensor {
+    type Err = Error;
 
-        ranges.add((10, 10));
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let point = |s: &mut Scanner| -> Result<Point2d> {
+            s.literal("x=")?;
+            let x = s.integer()?;
+            s.literal(", y=")?;
+            Ok(Point2d::new(x, s.integer()?))
+        };
 
-        assert_eq!(ranges.size(), 2);
-        assert_eq!(ranges.first_free_space(0, 10), Some(9));
+        let (location, closest_beacon) = Scanner::parse(str, |s| {
+            s.literal("Sensor at ")?;
+            let location = point(s)?;
+            s.literal(": closest beacon is at ")?;
+            Ok((location, point(s)?))
+        })?;
 
-        ranges.add((9, 11));
+        Ok(Sensor {
+            location,
+            closest_beacon,
+        })
+    }
+}
 
-        assert_eq!(ranges.size(), 4);
-        assert_eq!(ranges.first_free_space(0, 10), Some(8));
+/// Manhattan distance, in an `i64` since across the whole of `i32` it does
+/// not fit in an `i32` or even a `u32`.
+fn distance(a: &Point2d, b: &Point2d) -> i64 {
+    (a.x() as i64 - b.x() as i64).abs() + (a.y() as i64 - b.y() as i64).abs()
+}
 
-        ranges.add((10, 12));
+impl Sensor {
+    fn beacon_distance(&self) -> i64 {
+        distance(&self.location, &self.closest_beacon)
+    }
 
-        assert_eq!(ranges.size(), 5);
+    fn covers(&self, p: &Point2d) -> bool {
+        distance(&self.location, p) <= self.beacon_distance()
+    }
 
-        ranges.add((8, 10));
+    /// The x positions covered in row `y`, which may reach past the range of
+    /// an `i32`.
+    fn x_range(&self, y: i32) -> Option<(i64, i64)> {
+        let spare = self.beacon_distance() - (self.location.y() as i64 - y as i64).abs();
+        if spare < 0 {
+            return None;
+        }
 
-        assert_eq!(ranges.size(), 6);
-        assert_eq!(ranges.first_free_space(0, 10), Some(7));
+        let x = self.location.x() as i64;
+        Some((x - spare, x + spare))
+    }
+}
 
-        ranges.add((15, 20));
+#[cfg(test)]
+mod test {
+    use super::{intersect_boundaries, scan_rows, Day15, Sensor, Solver, Survey};
+    use crate::{
+        days::point::Point2d,
+        params::Params,
+        solution::{example_with, Program, Solution},
+    };
 
-        assert_eq!(ranges.size(), 12);
+    #[test]
+    fn examples() {
+        let mut params = Params::new();
+        params.insert("row", "10");
+        params.insert("search_max", "20");
+
+        for solver in [Solver::Rows, Solver::Boundaries] {
+            params.insert("solver", &solver.to_string());
+            assert_eq!(
+                example_with(Day15, include_str!("examples/day15.txt"), &params),
+                ("26".to_string(), "56000011".to_string())
+            );
+        }
 
-        ranges.add((11, 17));
+        params.insert("rwo", "10");
+        let lines = include_str!("examples/day15.txt")
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            Day15.solve_timed(lines, &params).unwrap_err().to_string(),
+            "day15 input: invalid value: unknown parameter rwo, \
+             expected one of row, search_min, search_max, solver"
+        );
+    }
 
-        assert_eq!(ranges.size(), 14);
+    #[test]
+    fn wide_sensors() {
+        // Reaches 4 billion either side, which does not fit in an i32.
+        let lines = vec![
+            "Sensor at x=-2000000000, y=0: closest beacon is at x=2000000000, y=0".to_string(),
+        ];
+        let mut params = Params::new();
+        params.insert("row", "0");
+        params.insert("search_max", "20");
+
+        for solver in [Solver::Rows, Solver::Boundaries] {
+            params.insert("solver", &solver.to_string());
+            let survey = Day15.parse_with(lines.clone(), &params).unwrap();
+            assert_eq!(Day15.part1(&survey), Ok(8_000_000_000));
+            assert_eq!(
+                Day15.part2(&survey).unwrap_err().to_string(),
+                "unsolvable input: no free space found"
+            );
+        }
+    }
 
-        ranges.add((0, 24));
+    #[test]
+    fn solvers_find_lone_gaps() {
+        // A small xorshift generator, so the surveys are the same every run.
+        let mut state = 0x2545f491u32;
+        let mut random = |n: i32| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state % n as u32) as i32
+        };
+
+        let mut lone_gaps = 0;
+        for _ in 0..20000 {
+            let sensors: Vec<_> = (0..3 + random(6))
+                .map(|_| {
+                    let location = Point2d::new(random(13) - 1, random(13) - 1);
+                    Sensor {
+                        closest_beacon: location.add_x(random(9) - 4).add_y(random(9) - 4),
+                        location,
+                    }
+                })
+                .collect();
+            let survey = Survey {
+                sensors,
+                row: 0,
+                search: (0, 10),
+                solver: Solver::Boundaries,
+            };
+
+            let gaps: Vec<_> = (0..=10)
+                .flat_map(|y| (0..=10).map(move |x| Point2d::new(x, y)))
+                .filter(|p| survey.sensors.iter().all(|s| !s.covers(p)))
+                .collect();
+            if let [gap] = &gaps[..] {
+                lone_gaps += 1;
+                assert_eq!(intersect_boundaries(&survey).as_ref(), Some(gap));
+                assert_eq!(scan_rows(&survey).as_ref(), Some(gap));
+            }
+        }
 
-        assert_eq!(ranges.size(), 25);
-        assert_eq!(ranges.first_free_space(0, 10), None);
+        assert!(lone_gaps > 100, "only {} surveys had a lone gap", lone_gaps);
     }
 }
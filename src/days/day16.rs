@@ -7,13 +7,34 @@ use std::{
     thread,
 };
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let valves = parse(&lines);
+use lazy_static::lazy_static;
+use regex::Regex;
 
-    part1(&valves);
-    part2(&valves);
+use super::search::{Graph, Search};
+use crate::{
+    error::{Error, Result},
+    solution::Solution,
+};
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u8 = 16;
+    type Parsed = Valves;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        parse(&lines)
+    }
 
-    Ok(())
+    fn part1(&self, valves: &Self::Parsed) -> Result<i32> {
+        Ok(parts(valves, 30, false))
+    }
+
+    fn part2(&self, valves: &Self::Parsed) -> Result<i32> {
+        Ok(parts(valves, 26, true))
+    }
 }
 
 #[derive(Debug)]
@@ -22,42 +43,68 @@ struct Valve {
     tunnels: Vec<String>,
 }
 
-fn parse(lines: &[String]) -> Valves {
-    let mut map = HashMap::new();
-
-    for line in lines {
-        let split: Vec<_> = line.split(' ').collect();
-        let id = split[1];
-        let rate_str = split[4];
-        let tunnel_strs = &split[9..];
-
-        let flow_rate = rate_str[5..rate_str.len() - 1].parse::<i32>().unwrap();
+pub fn parse(lines: &[String]) -> Result<Valves> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new("^Valve ([A-Z]+) has flow rate=([0-9]+); tunnels? leads? to valves? (.*)$")
+                .unwrap();
+    }
 
-        let tunnels = tunnel_strs
-            .iter()
-            .map(|s| s.strip_suffix(',').unwrap_or(s).to_string())
+    let mut parsed = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        let captures = RE.captures(line).ok_or_else(|| {
+            Error::parse("expected \"Valve <id> has flow rate=<n>; tunnels lead to valves <ids>\"")
+                .at_line(i, line)
+        })?;
+        let id = captures[1].to_string();
+        let flow_rate = captures[2]
+            .parse::<i32>()
+            .map_err(|e| Error::from(e).at_line(i, line))?;
+
+        let tunnels = captures[3]
+            .split(", ")
+            .map(|s| s.to_string())
             .collect::<Vec<_>>();
 
-        map.insert(id.to_string(), Valve { flow_rate, tunnels });
+        parsed.push((i, id, Valve { flow_rate, tunnels }));
+    }
+
+    let ids: HashSet<_> = parsed.iter().map(|(_, id, _)| id.as_str()).collect();
+    for (i, _, valve) in &parsed {
+        if let Some(tunnel) = valve.tunnels.iter().find(|t| !ids.contains(t.as_str())) {
+            return Err(
+                Error::invalid_value(format!("tunnel to unknown valve {}", tunnel))
+                    .at_line(*i, &lines[*i]),
+            );
+        }
     }
+    if !ids.contains("AA") {
+        return Err(Error::missing_section("valve AA"));
+    }
+
+    let map = parsed
+        .into_iter()
+        .map(|(_, id, valve)| (id, valve))
+        .collect();
 
     Valves::from_map(map)
 }
 
 #[derive(Debug)]
-struct Valves {
+pub struct Valves {
     dist_start: HashMap<String, i32>,
     dist_between: HashMap<(String, String), i32>,
     flows: HashMap<String, i32>,
 }
 
 impl Valves {
-    fn from_map(valves: HashMap<String, Valve>) -> Self {
+    fn from_map(valves: HashMap<String, Valve>) -> Result<Self> {
         let with_flow: Vec<_> = valves.iter().filter(|(_, v)| v.flow_rate > 0).collect();
         let mut dist_start = HashMap::new();
 
         for (v, _) in &with_flow {
-            let d = distance_between(&valves, "AA", v);
+            let d = distance_between(&valves, "AA", v)?;
             dist_start.insert((*v).to_owned(), d);
         }
 
@@ -66,7 +113,7 @@ impl Valves {
         for (a, _) in &with_flow {
             for (b, _) in &with_flow {
                 if a != b && !dist_between.contains_key(&((*a).to_owned(), (*b).to_owned())) {
-                    let d = distance_between(&valves, a, b);
+                    let d = distance_between(&valves, a, b)?;
 
                     dist_between.insert(((*a).to_owned(), (*b).to_owned()), d);
                     dist_between.insert(((*b).to_owned(), (*a).to_owned()), d);
@@ -80,37 +127,33 @@ impl Valves {
             flows.insert((*v).to_owned(), f.flow_rate);
         }
 
-        Valves {
+        Ok(Valves {
             dist_start,
             dist_between,
             flows,
-        }
+        })
     }
 }
 
-fn distance_between(valves: &HashMap<String, Valve>, from: &str, to: &str) -> i32 {
-    let mut visited = HashSet::new();
-    visited.insert(from);
-    let mut postions = VecDeque::new();
-    postions.push_front((from, 0));
-
-    while let Some((v, d)) = postions.pop_back() {
-        let valve = &valves[v];
-        for tunnel in &valve.tunnels {
-            if tunnel == to {
-                return d + 1;
-            } else {
-                postions.push_front((tunnel, d + 1));
-                visited.insert(tunnel);
-            }
-        }
-    }
+struct Tunnels<'a>(&'a HashMap<String, Valve>);
+
+impl<'a> Graph for Tunnels<'a> {
+    type Node = &'a str;
 
-    panic!("missing node")
+    fn neighbours(&self, valve: &&'a str) -> Vec<(&'a str, u32)> {
+        self.0[*valve]
+            .tunnels
+            .iter()
+            .map(|t| (t.as_str(), 1))
+            .collect()
+    }
 }
 
-fn part1(valves: &Valves) {
-    println!("Part 1: {}", parts(valves, 30, false))
+fn distance_between(valves: &HashMap<String, Valve>, from: &str, to: &str) -> Result<i32> {
+    Search::new()
+        .bfs(&Tunnels(valves), from, |v| *v == to)
+        .map(|found| found.cost as i32)
+        .ok_or_else(|| Error::unsolvable(format!("no tunnels lead from {} to {}", from, to)))
 }
 
 #[derive(Debug)]
@@ -123,10 +166,6 @@ struct StateElephant<'a> {
     elephant_time: i32,
 }
 
-fn part2(valves: &Valves) {
-    println!("Part 2: {}", parts(valves, 26, true))
-}
-
 fn parts(valves: &Valves, moves: i32, has_elephant: bool) -> i32 {
     let max_pressure = Arc::new(AtomicI32::new(0));
 
@@ -163,14 +202,13 @@ fn parts(valves: &Valves, moves: i32, has_elephant: bool) -> i32 {
 
     let batch_size = 100;
 
-    while let Some(next) = states.pop_front() {
-        if states.len() > num_threads * 100 {
+    // Expand breadth first until there is enough work to share out.
+    while states.len() <= num_threads * 100 {
+        let Some(next) = states.pop_front() else {
             break;
-        }
+        };
 
-        if next.pressure > max_pressure.load(Ordering::Relaxed) {
-            max_pressure.store(next.pressure, Ordering::Relaxed);
-        }
+        max_pressure.fetch_max(next.pressure, Ordering::Relaxed);
 
         let (time, current, is_elephant) =
             if !has_elephant || next.current_time > next.elephant_time {
@@ -241,9 +279,7 @@ fn parts(valves: &Valves, moves: i32, has_elephant: bool) -> i32 {
                 let mut new_states = Vec::new();
 
                 while let Some(next) = states.pop() {
-                    if next.pressure > max_pressure.load(Ordering::Relaxed) {
-                        max_pressure.store(next.pressure, Ordering::Relaxed);
-                    }
+                    max_pressure.fetch_max(next.pressure, Ordering::Relaxed);
                     let (time, current, is_elephant) =
                         if !has_elephant || next.current_time > next.elephant_time {
                             (next.current_time, next.current_pos, false)
@@ -295,3 +331,17 @@ fn parts(valves: &Valves, moves: i32, has_elephant: bool) -> i32 {
 
     max_pressure.load(Ordering::Relaxed)
 }
+
+#[cfg(test)]
+mod test {
+    use super::Day16;
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day16, include_str!("examples/day16.txt")),
+            ("1651".to_string(), "1707".to_string())
+        );
+    }
+}
@@ -1,204 +1,253 @@
-const TARGET_ROCKS: u64 = 1000000000000;
+use std::fs;
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let parsed: Vec<_> = lines[0]
-        .chars()
-        .map(|c| {
-            if c == '>' {
-                GasJet::Right
-            } else {
-                GasJet::Left
-            }
-        })
-        .collect();
-
-    let mut chamber = Chamber::new();
-    let mut blasts = Blasts::new(parsed.clone());
-
-    for r in 0..2022 {
-        let rock = match r % 5 {
-            0 => Rock::Horizontal,
-            1 => Rock::Plus,
-            2 => Rock::LShape,
-            3 => Rock::Verticle,
-            4 => Rock::Square,
-            _ => unreachable!(),
-        };
+use super::{cycle::find_cycle, grid::Grid, point::Point2d};
+use crate::{
+    error::{Error, Result},
+    params::Params,
+    solution::Solution,
+    visualize::Frame,
+};
 
-        chamber.drop(&rock, &mut blasts);
+const TARGET_ROCKS: u64 = 1000000000000;
+const CYCLE_LIMIT: usize = 100000;
+/// A row of the chamber, with bit `x` set if column `x` is filled.
+type Row = u16;
+const MAX_WIDTH: usize = Row::BITS as usize;
+/// How far below the top of the tower a rock may be able to fall before the
+/// space it could reach is too costly to remember, which happens when some
+/// column never fills up.
+const REACH_LIMIT: usize = 1000;
+
+/// The rocks from the puzzle, in the order they fall.
+const ROCKS: &str = "\
+####
+
+.#.
+###
+.#.
+
+..#
+..#
+###
+
+#
+#
+#
+#
+
+##
+##";
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+    const PARAMS: &'static [&'static str] = &["rocks", "width", "spawn_x", "spawn_y"];
+    type Parsed = Rules;
+    type Part1 = usize;
+    type Part2 = u64;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        self.parse_with(lines, &Params::new())
     }
 
-    let part1 = chamber.height();
-
-    println!("Part 1: {}", part1);
-
-    let mut blasts = Blasts::new(parsed);
-    let mut chamber = Chamber::new();
-
-    let mut last_height = 0;
-    let mut deltas = Vec::new();
+    /// Takes `rocks` as a file of shapes drawn like [`ROCKS`], `width` as the
+    /// chamber width, and `spawn_x` and `spawn_y` as how far a new rock
+    /// appears from the left wall and above the tower.
+    fn parse_with(&self, lines: Vec<String>, params: &Params) -> Result<Self::Parsed> {
+        let line = lines
+            .first()
+            .ok_or_else(|| Error::missing_section("jet pattern"))?;
+        if line.is_empty() {
+            return Err(Error::missing_section("jet pattern"));
+        }
 
-    for r in 0..10000 {
-        let rock = match r % 5 {
-            0 => Rock::Horizontal,
-            1 => Rock::Plus,
-            2 => Rock::LShape,
-            3 => Rock::Verticle,
-            4 => Rock::Square,
-            _ => unreachable!(),
+        let jets = line
+            .chars()
+            .map(|c| match c {
+                '>' => Ok(GasJet::Right),
+                '<' => Ok(GasJet::Left),
+                _ => Err(Error::parse(format!("unknown jet {:?}", c)).at_line(0, line)),
+            })
+            .collect::<Result<_>>()?;
+
+        let rocks = match params.get::<String>("rocks")? {
+            Some(path) => {
+                let text = fs::read_to_string(&path).map_err(|e| {
+                    Error::invalid_value(format!("could not read rocks from {}: {}", path, e))
+                })?;
+                Rock::parse_all(&text)?
+            }
+            None => Rock::parse_all(ROCKS)?,
         };
 
-        chamber.drop(&rock, &mut blasts);
-
-        let height = chamber.height();
+        Rules::new(
+            jets,
+            rocks,
+            params.get_or("width", 7)?,
+            (params.get_or("spawn_x", 2)?, params.get_or("spawn_y", 3)?),
+        )
+    }
 
-        deltas.push(height - last_height);
+    fn part1(&self, parsed: &Self::Parsed) -> Result<usize> {
+        Ok(simulate(parsed, 2022))
+    }
 
-        last_height = height;
+    fn part2(&self, parsed: &Self::Parsed) -> Result<u64> {
+        extrapolate(parsed, TARGET_ROCKS)
     }
 
-    let len = deltas.len();
+    /// Stacks the rocks for part 1, one frame per rock.
+    fn visualize(&self, parsed: &Self::Parsed, show: &mut dyn FnMut(Frame)) -> Result<bool> {
+        let mut tower = Tower::new(parsed);
+        show(tower.chamber.frame());
 
-    for j in (1..(len / 3)).rev() {
-        let mut matches = true;
-        for k in 0..j {
-            matches = deltas[len - 1 - k] == deltas[len - 1 - k - j];
-            matches = matches && deltas[len - 1 - k] == deltas[len - 1 - k - (2 * j)];
-            if !matches {
-                break;
-            }
+        for _ in 0..2022 {
+            tower.drop_next();
+            show(tower.chamber.frame());
         }
-        if matches {
-            let repetition = &deltas[len - j..];
 
-            let rep_len: u64 = repetition.len().try_into().unwrap();
-            let rep_height: u64 = repetition.iter().sum::<usize>().try_into().unwrap();
+        Ok(true)
+    }
+}
 
-            let mut remainder = deltas.clone();
-            while let Some(r) = remainder.strip_suffix(repetition) {
-                remainder = r.to_vec();
-            }
+/// The height of the tower after dropping `rocks` rocks one by one.
+fn simulate(rules: &Rules, rocks: usize) -> usize {
+    let mut tower = Tower::new(rules);
 
-            let rem_len: u64 = remainder.len().try_into().unwrap();
-            let rem_height: u64 = remainder.iter().sum::<usize>().try_into().unwrap();
+    for _ in 0..rocks {
+        tower.drop_next();
+    }
 
-            let reps = (TARGET_ROCKS - rem_len) / rep_len;
-            let rem = TARGET_ROCKS - rem_len - rep_len * (reps);
+    tower.chamber.height()
+}
 
-            let extra_height: u64 = repetition
-                .iter()
-                .take(rem as usize)
-                .sum::<usize>()
-                .try_into()
-                .unwrap();
+/// The height of the tower after `rocks` rocks, found by dropping them until
+/// the tower repeats itself.
+fn extrapolate(rules: &Rules, rocks: u64) -> Result<u64> {
+    let mut tower = Tower::new(rules);
+    let mut heights = vec![0];
 
-            println!("Part 2: {}", rem_height + reps * rep_height + extra_height);
+    let step = |tower: &mut Tower| {
+        tower.drop_next();
+        heights.push(tower.chamber.height());
+    };
+    let cycle = find_cycle(&mut tower, CYCLE_LIMIT, step, Tower::key)
+        .ok_or_else(|| Error::unsolvable("the tower never repeats itself"))?;
 
-            break;
-        }
-    }
+    let (rocks, periods) = cycle.reduce(rocks);
+    let per_period = heights[cycle.start + cycle.period] - heights[cycle.start];
 
-    Ok(())
+    Ok(heights[rocks] as u64 + periods * per_period as u64)
 }
 
 #[derive(Debug, Clone, Copy)]
-enum GasJet {
+pub enum GasJet {
     Left,
     Right,
 }
 
-#[derive(Debug)]
-enum Rock {
-    Horizontal,
-    Plus,
-    LShape,
-    Verticle,
-    Square,
+/// The jets, the rocks in the order they fall, and the chamber they fall into.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    jets: Vec<GasJet>,
+    rocks: Vec<Rock>,
+    width: usize,
+    spawn: (usize, usize),
 }
 
-impl Rock {
-    fn left(&self, x: usize) -> usize {
-        if x == 0 {
-            0
-        } else {
-            x - 1
+impl Rules {
+    fn new(
+        jets: Vec<GasJet>,
+        rocks: Vec<Rock>,
+        width: usize,
+        spawn: (usize, usize),
+    ) -> Result<Self> {
+        if !(1..=MAX_WIDTH).contains(&width) {
+            return Err(Error::invalid_value(format!(
+                "the chamber must be between 1 and {} wide, not {}",
+                MAX_WIDTH, width
+            )));
         }
+        if let Some(rock) = rocks.iter().find(|r| spawn.0 + r.width > width) {
+            return Err(Error::invalid_value(format!(
+                "a rock {} wide does not fit {} from the wall of a chamber {} wide",
+                rock.width, spawn.0, width
+            )));
+        }
+
+        Ok(Rules {
+            jets,
+            rocks,
+            width,
+            spawn,
+        })
     }
+}
 
-    fn right(&self, x: usize) -> usize {
-        let min = match self {
-            Rock::Horizontal => 7 - 4,
-            Rock::Plus => 7 - 3,
-            Rock::LShape => 7 - 3,
-            Rock::Verticle => 7 - 1,
-            Rock::Square => 7 - 2,
-        };
-        (x + 1).min(min)
+/// A rock as rows from the bottom up, pushed against the left wall.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rock {
+    rows: Vec<Row>,
+    width: usize,
+}
+
+impl Rock {
+    /// Reads rocks drawn with `#` and `.`, top row first, with a blank line
+    /// between each.
+    fn parse_all(text: &str) -> Result<Vec<Rock>> {
+        let lines: Vec<_> = text.lines().map(str::trim_end).collect();
+
+        let rocks: Vec<_> = lines
+            .split(|l| l.is_empty())
+            .filter(|drawing| !drawing.is_empty())
+            .map(Rock::parse)
+            .collect::<Result<_>>()?;
+
+        if rocks.is_empty() {
+            return Err(Error::missing_section("rocks"));
+        }
+        Ok(rocks)
     }
 
-    fn draw(&self, x: usize) -> Vec<[bool; 7]> {
-        let mut vec = Vec::new();
-        match self {
-            Rock::Horizontal => {
-                let mut row = [false; 7];
-                for i in 0..4 {
-                    row[x + i] = true;
-                }
-                vec.push(row);
-            }
-            Rock::Plus => {
-                let mut row1 = [false; 7];
-                row1[x + 1] = true;
-                vec.push(row1);
-                let mut row2 = [false; 7];
-                for i in 0..3 {
-                    row2[x + i] = true;
-                }
-                vec.push(row2);
-                let mut row3 = [false; 7];
-                row3[x + 1] = true;
-                vec.push(row3);
-            }
-            Rock::LShape => {
-                let mut row1 = [false; 7];
-                for i in 0..3 {
-                    row1[x + i] = true;
+    fn parse(drawing: &[&str]) -> Result<Rock> {
+        let mut cells = Vec::new();
+        for (row, line) in drawing.iter().rev().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '#' => cells.push((x, row)),
+                    '.' => {}
+                    _ => {
+                        return Err(Error::invalid_value(format!(
+                            "unknown rock cell {:?} in {:?}",
+                            c, line
+                        )))
+                    }
                 }
-                vec.push(row1);
-                let mut row2 = [false; 7];
-                row2[x + 2] = true;
-                vec.push(row2);
-                let mut row3 = [false; 7];
-                row3[x + 2] = true;
-                vec.push(row3);
-            }
-            Rock::Verticle => {
-                let mut row1 = [false; 7];
-                row1[x] = true;
-                vec.push(row1);
-                let mut row2 = [false; 7];
-                row2[x] = true;
-                vec.push(row2);
-                let mut row3 = [false; 7];
-                row3[x] = true;
-                vec.push(row3);
-                let mut row4 = [false; 7];
-                row4[x] = true;
-                vec.push(row4);
-            }
-            Rock::Square => {
-                let mut row1 = [false; 7];
-                row1[x] = true;
-                row1[x + 1] = true;
-                vec.push(row1);
-                let mut row2 = [false; 7];
-                row2[x] = true;
-                row2[x + 1] = true;
-                vec.push(row2);
             }
-        };
+        }
+
+        let min_x = cells.iter().map(|c| c.0).min();
+        let min_y = cells.iter().map(|c| c.1).min();
+        let (min_x, min_y) = min_x
+            .zip(min_y)
+            .ok_or_else(|| Error::invalid_value(format!("empty rock {:?}", drawing.join("/"))))?;
+
+        let width = cells.iter().map(|c| c.0 + 1 - min_x).max().unwrap_or(0);
+        let height = cells.iter().map(|c| c.1 + 1 - min_y).max().unwrap_or(0);
+        if width > MAX_WIDTH {
+            return Err(Error::invalid_value(format!(
+                "rocks can be at most {} wide, not {}",
+                MAX_WIDTH, width
+            )));
+        }
+
+        let mut rows = vec![0; height];
+        for (x, y) in cells {
+            rows[y - min_y] |= 1 << (x - min_x);
+        }
 
-        vec
+        Ok(Rock { rows, width })
     }
 }
 
@@ -219,100 +268,339 @@ impl Blasts {
     }
 }
 
+/// Rocks falling one after another into a chamber.
+struct Tower<'a> {
+    rules: &'a Rules,
+    chamber: Chamber,
+    blasts: Blasts,
+    rocks: usize,
+}
+
+impl<'a> Tower<'a> {
+    fn new(rules: &'a Rules) -> Self {
+        Tower {
+            rules,
+            chamber: Chamber::new(rules.width),
+            blasts: Blasts::new(rules.jets.clone()),
+            rocks: 0,
+        }
+    }
+
+    fn drop_next(&mut self) {
+        let rock = &self.rules.rocks[self.rocks % self.rules.rocks.len()];
+        self.chamber.drop(rock, &mut self.blasts, self.rules.spawn);
+        self.rocks += 1;
+    }
+
+    /// Everything that decides how the tower grows from here on, unless a
+    /// rock could fall further than [`REACH_LIMIT`] below its top.
+    fn key(&self) -> Option<(usize, usize, Vec<Row>)> {
+        Some((
+            self.rocks % self.rules.rocks.len(),
+            self.blasts.pointer,
+            self.chamber.reachable(REACH_LIMIT)?,
+        ))
+    }
+}
+
+/// The settled rocks, without the rows below the last one that was filled
+/// from wall to wall, since nothing can reach past it.
 #[derive(Debug)]
 struct Chamber {
-    occupied: Vec<[bool; 7]>,
+    width: usize,
+    occupied: Vec<Row>,
+    pruned: usize,
 }
 
 impl Chamber {
-    fn new() -> Self {
+    fn new(width: usize) -> Self {
         Chamber {
+            width,
             occupied: Vec::new(),
+            pruned: 0,
         }
     }
 
-    fn drop(&mut self, rock: &Rock, blasts: &mut Blasts) {
-        let mut x = 2;
+    fn drop(&mut self, rock: &Rock, blasts: &mut Blasts, (spawn_x, spawn_y): (usize, usize)) {
+        let mut shape: Vec<Row> = rock.rows.iter().map(|r| r << spawn_x).collect();
+        let mut y = self.occupied.len() + spawn_y;
+        let right_wall: Row = 1 << (self.width - 1);
+
+        loop {
+            match blasts.next() {
+                GasJet::Left if shape.iter().all(|r| r & 1 == 0) => {
+                    shape.iter_mut().for_each(|r| *r >>= 1);
+                    if !self.fits(&shape, y) {
+                        shape.iter_mut().for_each(|r| *r <<= 1);
+                    }
+                }
+                GasJet::Right if shape.iter().all(|r| r & right_wall == 0) => {
+                    shape.iter_mut().for_each(|r| *r <<= 1);
+                    if !self.fits(&shape, y) {
+                        shape.iter_mut().for_each(|r| *r >>= 1);
+                    }
+                }
+                _ => {}
+            }
 
-        let empty = self
-            .occupied
-            .iter()
-            .rev()
-            .take_while(|r| **r == [false; 7])
-            .count();
+            match y.checked_sub(1).filter(|&y| self.fits(&shape, y)) {
+                Some(below) => y = below,
+                None => break,
+            }
+        }
 
-        let to_add = 4 - empty;
+        let top = y + shape.len();
+        if self.occupied.len() < top {
+            self.occupied.resize(top, 0);
+        }
+        for (row, r) in self.occupied[y..top].iter_mut().zip(&shape) {
+            *row |= r;
+        }
 
-        for _ in 0..to_add {
-            self.occupied.push([false; 7]);
+        let full = Row::MAX >> (MAX_WIDTH - self.width);
+        if let Some(floor) = (y..top).rev().find(|&y| self.occupied[y] == full) {
+            self.occupied.drain(..=floor);
+            self.pruned += floor + 1;
         }
+    }
 
-        for i in (0..self.occupied.len()).rev() {
-            let direction = blasts.next();
-            let next_x = match direction {
-                GasJet::Left => rock.left(x),
-                GasJet::Right => rock.right(x),
-            };
-
-            let draw = rock.draw(next_x);
-
-            let mut conflicts = false;
-            for (x, rock_row) in draw.iter().enumerate() {
-                if let Some(row) = self.occupied.get(i + x) {
-                    conflicts = row.iter().zip(rock_row).any(|(l, r)| *l && *r);
-                    if conflicts {
-                        break;
-                    }
-                }
+    /// Whether `shape` with its bottom row at `y` is clear of every other
+    /// rock.
+    fn fits(&self, shape: &[Row], y: usize) -> bool {
+        shape
+            .iter()
+            .zip(y..)
+            .all(|(r, y)| self.occupied.get(y).is_none_or(|row| row & r == 0))
+    }
+
+    /// The rows still kept, with a few empty ones above for the next rock
+    /// to fall through, and the floor if nothing has been pruned yet.
+    fn frame(&self) -> Frame {
+        let width = self.width as i32;
+        let top = self.height() as i32;
+        let mut grid = Grid::new();
+
+        for height in self.pruned as i32..top + 4 {
+            let y = -height - 1;
+            grid.insert(Point2d::new(-1, y), '|');
+            grid.insert(Point2d::new(width, y), '|');
+            let row = self.occupied.get(height as usize - self.pruned);
+            for x in 0..width {
+                let filled = row.is_some_and(|row| row >> x & 1 == 1);
+                grid.insert(Point2d::new(x, y), if filled { '#' } else { '.' });
+            }
+        }
+        if self.pruned == 0 {
+            grid.insert(Point2d::new(-1, 0), '+');
+            grid.insert(Point2d::new(width, 0), '+');
+            for x in 0..width {
+                grid.insert(Point2d::new(x, 0), '-');
             }
+        }
 
-            if !conflicts {
-                x = next_x;
+        Frame::new(grid).focused_on(Point2d::new(width / 2, -top))
+    }
+
+    /// The empty cells that can be reached from above the tower, as a mask
+    /// for each row from the empty one on top downwards, or `None` if they
+    /// go more than `limit` rows down. Falling rocks only ever move through
+    /// these cells, and every cell next to them is a rock, a wall or the
+    /// floor, so they decide everything about where later rocks come to
+    /// rest. Rocks can be pushed under overhangs, which is why this has to
+    /// follow the gaps rather than stop at the column tops.
+    fn reachable(&self, limit: usize) -> Option<Vec<Row>> {
+        let top = self.occupied.len();
+        let is_empty = |y: usize, x: usize| self.occupied.get(y).is_none_or(|r| r >> x & 1 == 0);
+
+        let mut reachable: Vec<Row> = vec![0];
+        let mut stack: Vec<_> = (0..self.width).map(|x| (top, x)).collect();
+        while let Some((y, x)) = stack.pop() {
+            let depth = top - y;
+            if depth > limit {
+                return None;
+            }
+            if depth == reachable.len() {
+                reachable.push(0);
             }
+            if reachable[depth] >> x & 1 == 1 || !is_empty(y, x) {
+                continue;
+            }
+            reachable[depth] |= 1 << x;
 
-            let draw = rock.draw(x);
-            let mut conflicts = false;
-            if i > 0 {
-                for (x, row_rock) in draw.iter().enumerate() {
-                    if let Some(row) = self.occupied.get(i - 1 + x) {
-                        conflicts = row.iter().zip(row_rock).any(|(l, r)| *l && *r);
-                        if conflicts {
-                            break;
-                        }
-                    }
-                }
+            if x > 0 {
+                stack.push((y, x - 1));
+            }
+            if x + 1 < self.width {
+                stack.push((y, x + 1));
             }
+            if y > 0 {
+                stack.push((y - 1, x));
+            }
+            if y < top {
+                stack.push((y + 1, x));
+            }
+        }
 
-            if i == 0 || conflicts {
-                for (x, row) in draw.iter().enumerate() {
-                    for (j, r) in row.iter().enumerate() {
-                        self.occupied[i + x][j] = self.occupied[i + x][j] || *r;
-                    }
-                }
+        while reachable.last() == Some(&0) {
+            reachable.pop();
+        }
+        Some(reachable)
+    }
 
-                break;
-            }
+    fn height(&self) -> usize {
+        self.pruned + self.occupied.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use super::{extrapolate, simulate, Chamber, Day17, GasJet, Rock, Rules, Tower, ROCKS};
+    use crate::{params::Params, solution::example, visualize::Animation, Solution};
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day17, include_str!("examples/day17.txt")),
+            ("3068".to_string(), "1514285714288".to_string())
+        );
+    }
+
+    #[test]
+    fn rocks() {
+        let rocks = Rock::parse_all(ROCKS).unwrap();
+        let sizes: Vec<_> = rocks.iter().map(|r| (r.width, r.rows.len())).collect();
+        assert_eq!(sizes, [(4, 1), (3, 3), (3, 3), (1, 4), (2, 2)]);
+        assert_eq!(rocks[1].rows, [0b010, 0b111, 0b010]);
+        assert_eq!(rocks[2].rows, [0b111, 0b100, 0b100]);
+
+        let shifted = Rock::parse_all("...\n.##\n").unwrap();
+        assert_eq!(shifted, Rock::parse_all("##").unwrap());
+
+        assert!(Rock::parse_all("\n\n").is_err());
+        assert!(Rock::parse_all("#x").is_err());
+        assert!(Rock::parse_all("..").is_err());
+        assert!(Rock::parse_all(&"#".repeat(17)).is_err());
+    }
+
+    #[test]
+    fn chamber_settings() {
+        let lines = vec![include_str!("examples/day17.txt").trim().to_string()];
+        let mut params = Params::new();
+        params.insert("width", "4");
+        params.insert("spawn_x", "1");
+        assert!(Day17.parse_with(lines.clone(), &params).is_err());
+
+        params.insert("width", "17");
+        assert!(Day17.parse_with(lines.clone(), &params).is_err());
+
+        params.insert("width", "4");
+        params.insert("spawn_x", "0");
+        let rules = Day17.parse_with(lines, &params).unwrap();
+        assert_eq!(rules.width, 4);
+        assert_eq!(rules.spawn, (0, 3));
+    }
+
+    #[test]
+    fn prunes_below_full_rows() {
+        let jets = vec![GasJet::Left, GasJet::Right];
+        let rocks = Rock::parse_all("####\n\n##\n##").unwrap();
+        let rules = Rules::new(jets, rocks, 4, (0, 3)).unwrap();
+        let mut tower = Tower::new(&rules);
+
+        for _ in 0..1000 {
+            tower.drop_next();
+            assert!(tower.chamber.occupied.len() <= 2);
         }
+        assert_eq!(tower.chamber.height(), 500 + 1000);
+        assert_eq!(tower.chamber.height(), simulate(&rules, 1000));
+    }
+
+    #[test]
+    fn reachable_space() {
+        let chamber = |occupied: Vec<u16>| Chamber {
+            width: 2,
+            occupied,
+            pruned: 0,
+        };
+
+        // An overhang over column 1 that column 0's top is below:
+        //   .#
+        //   ..
+        //   #.
+        //   ..
+        let overhang = chamber(vec![0b00, 0b01, 0b00, 0b10]);
+        assert_eq!(
+            overhang.reachable(10),
+            Some(vec![0b11, 0b01, 0b11, 0b10, 0b11])
+        );
+        assert_eq!(overhang.reachable(3), None);
+
+        // What is sealed off does not matter.
+        let sealed = |bottom| chamber(vec![bottom, 0b11, 0b00]);
+        assert_eq!(sealed(0b00).reachable(10), Some(vec![0b11, 0b11]));
+        assert_eq!(sealed(0b00).reachable(10), sealed(0b10).reachable(10));
+
+        assert_eq!(chamber(Vec::new()).reachable(10), Some(vec![0b11]));
+    }
+
+    #[test]
+    fn frames() {
+        let jets = vec![GasJet::Right];
+        let rocks = Rock::parse_all("###").unwrap();
+        let rules = Rules::new(jets, rocks, 4, (0, 1)).unwrap();
+        let mut tower = Tower::new(&rules);
+        tower.drop_next();
+
+        let animation = Animation {
+            fps: NonZeroU32::new(1).unwrap(),
+            viewport: None,
+        };
+        let frame = animation.render(&tower.chamber.frame());
+        assert_eq!(frame, "|....|\n|....|\n|....|\n|....|\n|.###|\n+----+\n");
     }
 
-    #[allow(dead_code)]
-    fn print(&self) {
-        for x in self.occupied.iter().rev() {
-            for b in x {
-                if *b {
-                    print!("#")
+    #[test]
+    fn extrapolates_variants() {
+        let jets: Vec<_> = include_str!("examples/day17.txt")
+            .trim()
+            .chars()
+            .map(|c| {
+                if c == '<' {
+                    GasJet::Left
                 } else {
-                    print!(" ")
+                    GasJet::Right
                 }
+            })
+            .collect();
+        let tetris = "####\n\n##\n##\n\n#.\n##\n.#\n\n###\n.#.\n\n#..\n###";
+        let variants = [
+            (ROCKS, 7, (2, 3)),
+            (ROCKS, 9, (3, 1)),
+            ("#", 1, (0, 0)),
+            (tetris, 8, (2, 3)),
+            (tetris, 6, (1, 2)),
+            (tetris, 5, (0, 5)),
+        ];
+
+        for (rocks, width, spawn) in variants {
+            let rocks = Rock::parse_all(rocks).unwrap();
+            let rules = Rules::new(jets.clone(), rocks, width, spawn).unwrap();
+            for n in [0, 1, 7, 500, 2022, 3001] {
+                assert_eq!(
+                    extrapolate(&rules, n as u64).unwrap(),
+                    simulate(&rules, n) as u64,
+                    "{} rocks in a chamber {} wide",
+                    n,
+                    width
+                );
             }
-            println!()
         }
-    }
 
-    fn height(&self) -> usize {
-        self.occupied
-            .iter()
-            .take_while(|x| x.iter().any(|b| *b))
-            .count()
+        // One column never fills, so the tower never repeats.
+        let rocks = Rock::parse_all(tetris).unwrap();
+        let rules = Rules::new(jets, rocks, 10, (4, 3)).unwrap();
+        assert!(extrapolate(&rules, 5000).is_err());
     }
 }
@@ -1,123 +1,137 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, str::FromStr};
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let parsed = lines
-        .iter()
-        .map(|l| Cube::from_str(l))
-        .collect::<Result<Vec<_>, _>>()?;
+use crate::{
+    error::{parse_lines, Error, Result},
+    solution::Solution,
+};
 
-    let mut count = 0;
+pub struct Day18;
 
-    for a in &parsed {
-        for b in &parsed {
-            if a.is_adjacent(b) {
-                count += 1;
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+    type Parsed = Vec<Cube>;
+    type Part1 = usize;
+    type Part2 = i32;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        parse_lines(&lines, Cube::from_str)
+    }
+
+    fn part1(&self, parsed: &Self::Parsed) -> Result<usize> {
+        let mut count = 0;
+
+        for a in parsed {
+            for b in parsed {
+                if a.is_adjacent(b) {
+                    count += 1;
+                }
             }
         }
+
+        let total = parsed.len() * 6;
+        Ok(total - count)
     }
 
-    let total = parsed.len() * 6;
-    println!("Part 1: {}", total - count);
-
-    let as_set: HashSet<_> = parsed.iter().collect();
-    let min_x = as_set.iter().min_by_key(|c| c.x).copied().unwrap().x;
-    let min_y = as_set.iter().min_by_key(|c| c.y).copied().unwrap().y;
-    let min_z = as_set.iter().min_by_key(|c| c.z).copied().unwrap().z;
-    let max_x = as_set.iter().max_by_key(|c| c.x).copied().unwrap().x;
-    let max_y = as_set.iter().max_by_key(|c| c.y).copied().unwrap().y;
-    let max_z = as_set.iter().max_by_key(|c| c.z).copied().unwrap().z;
-
-    let mut negative = HashSet::new();
-
-    let lower_bound = 1;
-    let upper_bound = 2;
-
-    negative.insert(Cube {
-        x: min_x - lower_bound,
-        y: min_y - lower_bound,
-        z: min_z - lower_bound,
-    });
-
-    let mut size = 0;
-
-    loop {
-        for x in min_x - lower_bound..max_x + upper_bound {
-            for y in min_y - lower_bound..max_y + upper_bound {
-                for z in min_z - lower_bound..max_z + upper_bound {
-                    let cube = Cube { x, y, z };
-                    if !as_set.contains(&cube) {
-                        let mut neighbour = false;
-                        for c in &negative {
-                            if c.is_adjacent(&cube) {
-                                neighbour = true;
-                                break;
+    fn part2(&self, parsed: &Self::Parsed) -> Result<i32> {
+        if parsed.is_empty() {
+            return Ok(0);
+        }
+
+        let as_set: HashSet<_> = parsed.iter().collect();
+        let min_x = as_set.iter().min_by_key(|c| c.x).copied().unwrap().x;
+        let min_y = as_set.iter().min_by_key(|c| c.y).copied().unwrap().y;
+        let min_z = as_set.iter().min_by_key(|c| c.z).copied().unwrap().z;
+        let max_x = as_set.iter().max_by_key(|c| c.x).copied().unwrap().x;
+        let max_y = as_set.iter().max_by_key(|c| c.y).copied().unwrap().y;
+        let max_z = as_set.iter().max_by_key(|c| c.z).copied().unwrap().z;
+
+        let mut negative = HashSet::new();
+
+        let lower_bound = 1;
+        let upper_bound = 2;
+
+        negative.insert(Cube {
+            x: min_x - lower_bound,
+            y: min_y - lower_bound,
+            z: min_z - lower_bound,
+        });
+
+        let mut size = 0;
+
+        loop {
+            for x in min_x - lower_bound..max_x + upper_bound {
+                for y in min_y - lower_bound..max_y + upper_bound {
+                    for z in min_z - lower_bound..max_z + upper_bound {
+                        let cube = Cube { x, y, z };
+                        if !as_set.contains(&cube) {
+                            let mut neighbour = false;
+                            for c in &negative {
+                                if c.is_adjacent(&cube) {
+                                    neighbour = true;
+                                    break;
+                                }
+                            }
+                            if neighbour {
+                                negative.insert(cube);
                             }
-                        }
-                        if neighbour {
-                            negative.insert(cube);
                         }
                     }
                 }
             }
+            let new_size = negative.len();
+            if new_size == size {
+                break;
+            } else {
+                size = new_size;
+            }
         }
-        let new_size = negative.len();
-        if new_size == size {
-            break;
-        } else {
-            size = new_size;
-        }
-    }
 
-    let mut count = 0;
+        let mut count = 0;
 
-    for a in &negative {
-        for b in &negative {
-            if a.is_adjacent(b) {
-                count += 1;
+        for a in &negative {
+            for b in &negative {
+                if a.is_adjacent(b) {
+                    count += 1;
+                }
             }
         }
-    }
 
-    let x_side = max_x - min_x + lower_bound + upper_bound;
-    let y_side = max_y - min_y + lower_bound + upper_bound;
-    let z_side = max_z - min_z + lower_bound + upper_bound;
+        let x_side = max_x - min_x + lower_bound + upper_bound;
+        let y_side = max_y - min_y + lower_bound + upper_bound;
+        let z_side = max_z - min_z + lower_bound + upper_bound;
 
-    let total = (negative.len() * 6) as i32 - count;
-    let exterior = (x_side * y_side * 2) + (x_side * z_side * 2) + (z_side * y_side * 2);
+        let total = (negative.len() * 6) as i32 - count;
+        let exterior = (x_side * y_side * 2) + (x_side * z_side * 2) + (z_side * y_side * 2);
 
-    println!("Part 2: {}", total - exterior);
-
-    Ok(())
+        Ok(total - exterior)
+    }
 }
 
 #[derive(Hash, Eq, PartialEq, Debug)]
-struct Cube {
+pub struct Cube {
     x: i32,
     y: i32,
     z: i32,
 }
 
-impl Cube {
-    fn from_str(str: &str) -> Result<Self, String> {
-        let mut split = str.split(',');
-
-        let x = split
-            .next()
-            .and_then(|p| p.parse::<i32>().ok())
-            .ok_or_else(|| "could not parse x".to_owned())?;
-
-        let y = split
-            .next()
-            .and_then(|p| p.parse::<i32>().ok())
-            .ok_or_else(|| "could not parse x".to_owned())?;
-        let z = split
-            .next()
-            .and_then(|p| p.parse::<i32>().ok())
-            .ok_or_else(|| "could not parse x".to_owned())?;
-
-        Ok(Cube { x, y, z })
+impl FromStr for Cube {
+    type Err = Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let split: Vec<_> = str.split(',').collect();
+
+        match split[..] {
+            [x, y, z] => Ok(Cube {
+                x: x.parse()?,
+                y: y.parse()?,
+                z: z.parse()?,
+            }),
+            _ => Err(Error::parse("expected \"<x>,<y>,<z>\"")),
+        }
     }
+}
 
+impl Cube {
     fn is_adjacent(&self, other: &Cube) -> bool {
         let (a, b) = if self.x == other.x && self.y == other.y {
             (self.z, other.z)
@@ -132,3 +146,17 @@ impl Cube {
         a.abs_diff(b) == 1
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Day18;
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day18, include_str!("examples/day18.txt")),
+            ("64".to_string(), "58".to_string())
+        );
+    }
+}
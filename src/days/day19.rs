@@ -1,30 +1,156 @@
-use std::collections::{hash_map::Entry, HashMap, HashSet};
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        OnceLock,
+    },
+    thread,
+};
+
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{
+    error::{parse_lines, Error, Result},
+    params::Params,
+    solution::Solution,
+};
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u8 = 19;
+    const PARAMS: &'static [&'static str] = &["plans"];
+    type Parsed = Blueprints;
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        self.parse_with(lines, &Params::new())
+    }
+
+    /// Takes `plans=true` to add the robots each blueprint builds to the
+    /// details.
+    fn parse_with(&self, lines: Vec<String>, params: &Params) -> Result<Self::Parsed> {
+        Ok(Blueprints {
+            blueprints: parse_lines(&lines, Blueprint::from_str)?,
+            plans: params.get_or("plans", false)?,
+            found: Default::default(),
+        })
+    }
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let blueprints: Vec<_> = lines.iter().map(|l| Blueprint::from_str(l)).collect();
+    fn part1(&self, parsed: &Self::Parsed) -> Result<u32> {
+        Ok(parsed
+            .part1_plans()
+            .iter()
+            .map(|(id, plan)| id * plan.geodes)
+            .sum())
+    }
 
-    let start_robots = HashMap::from([(Resource::Ore, 1)]);
-    let time = 24;
-    let mut total_quality: u32 = 0;
-    for b in &blueprints {
-        let best = b.simulate(time, &start_robots);
-        total_quality += best as u32 * b.id as u32;
+    fn part2(&self, parsed: &Self::Parsed) -> Result<u32> {
+        Ok(parsed
+            .part2_plans()
+            .iter()
+            .map(|(_, plan)| plan.geodes)
+            .product())
     }
-    println!("Part 1: {}", total_quality);
 
-    let time = 32;
-    let mut quality_product = 1;
-    for b in blueprints.iter().take(3) {
-        let best = b.simulate(time, &start_robots);
-        quality_product *= best as u32;
+    /// Every blueprint's geodes and quality level in id order, followed by
+    /// the plans for both parts if `plans=true` was given.
+    fn details(&self, parsed: &Self::Parsed) -> Result<Vec<String>> {
+        let mut details: Vec<_> = parsed
+            .part1_plans()
+            .iter()
+            .map(|(id, plan)| {
+                let builds = if parsed.plans {
+                    plan.build_order()
+                } else {
+                    String::new()
+                };
+                format!(
+                    "Blueprint {} ({} minutes): {} geodes, quality level {}{}",
+                    id,
+                    PART1_TIME,
+                    plan.geodes,
+                    id * plan.geodes,
+                    builds
+                )
+            })
+            .collect();
+
+        if parsed.plans {
+            details.extend(
+                parsed.part2_plans().iter().map(|(id, plan)| {
+                    format!("Blueprint {} ({} minutes): {}", id, PART2_TIME, plan)
+                }),
+            );
+        }
+
+        Ok(details)
     }
+}
+
+const PART1_TIME: u32 = 24;
+const PART2_TIME: u32 = 32;
+
+/// The best plan for each blueprint in `time` minutes, by id. The blueprints
+/// are independent, so each thread takes the next one not yet started.
+fn best_plans(blueprints: &[Blueprint], time: u32) -> Vec<(u32, Plan)> {
+    let num_threads = thread::available_parallelism()
+        .map(|u| u.get())
+        .unwrap_or(1)
+        .min(blueprints.len());
+    let next = AtomicUsize::new(0);
+
+    let mut plans: Vec<_> = thread::scope(|s| {
+        let workers: Vec<_> = (0..num_threads)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut plans = Vec::new();
+                    while let Some(b) = blueprints.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        plans.push((b.id, b.best_plan(time)));
+                    }
+                    plans
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    });
 
-    println!("Part 2: {}", quality_product);
+    plans.sort_by_key(|&(id, _)| id);
+    plans
+}
 
-    Ok(())
+pub struct Blueprints {
+    blueprints: Vec<Blueprint>,
+    plans: bool,
+    /// The best plans for each part, kept so the details do not search for
+    /// them again.
+    found: [OnceLock<Vec<(u32, Plan)>>; 2],
+}
+
+impl Blueprints {
+    fn part1_plans(&self) -> &[(u32, Plan)] {
+        self.found[0].get_or_init(|| best_plans(&self.blueprints, PART1_TIME))
+    }
+
+    /// Only the first three blueprints, which are all that survive part 2.
+    fn part2_plans(&self) -> &[(u32, Plan)] {
+        self.found[1].get_or_init(|| {
+            let first = &self.blueprints[..self.blueprints.len().min(3)];
+            best_plans(first, PART2_TIME)
+        })
+    }
 }
 
-#[derive(Hash, PartialEq, PartialOrd, Ord, Eq, Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Resource {
     Ore,
     Clay,
@@ -32,241 +158,351 @@ enum Resource {
     Geode,
 }
 
-#[derive(Debug)]
-struct Cost(HashMap<Resource, u8>);
-
-impl Cost {
-    fn single(resource: Resource, amount: u8) -> Self {
-        Cost(HashMap::from([(resource, amount)]))
+impl Resource {
+    /// Most valuable first, which is also the best order to try building
+    /// robots in.
+    const ALL: [Resource; 4] = [
+        Resource::Geode,
+        Resource::Obsidian,
+        Resource::Clay,
+        Resource::Ore,
+    ];
+
+    fn index(&self) -> usize {
+        *self as usize
     }
+}
 
-    fn double(resource1: Resource, amount1: u8, resource2: Resource, amount2: u8) -> Self {
-        Cost(HashMap::from([(resource1, amount1), (resource2, amount2)]))
+impl Display for Resource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Resource::Ore => write!(f, "ore"),
+            Resource::Clay => write!(f, "clay"),
+            Resource::Obsidian => write!(f, "obsidian"),
+            Resource::Geode => write!(f, "geode"),
+        }
     }
 }
 
-#[derive(Debug)]
-struct Blueprint {
-    id: i32,
-    ore_cost: Cost,
-    clay_cost: Cost,
-    obsidian_cost: Cost,
-    geode_cost: Cost,
+#[derive(Debug, Clone)]
+pub struct Blueprint {
+    id: u32,
+    /// What each kind of robot costs, indexed by [`Resource::index`].
+    costs: [[u32; 4]; 4],
 }
 
-impl Blueprint {
-    fn from_str(str: &str) -> Self {
-        let mut first_split = str.split(": ");
-        let id = first_split
-            .next()
-            .unwrap()
-            .split(' ')
-            .last()
-            .unwrap()
-            .parse::<i32>()
+impl FromStr for Blueprint {
+    type Err = Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        lazy_static! {
+            static ref RE: Regex = Regex::new(
+                "^Blueprint ([0-9]+): \
+                 Each ore robot costs ([0-9]+) ore. \
+                 Each clay robot costs ([0-9]+) ore. \
+                 Each obsidian robot costs ([0-9]+) ore and ([0-9]+) clay. \
+                 Each geode robot costs ([0-9]+) ore and ([0-9]+) obsidian.$"
+            )
             .unwrap();
+        }
 
-        let mut second_split = first_split.next().unwrap().split(". ");
-
-        let mut ore_cost_str = second_split.next().unwrap().split(' ');
-        let mut clay_cost_str = second_split.next().unwrap().split(' ');
-        let mut obsidian_cost_str = second_split.next().unwrap().split(' ');
-        let mut geode_cost_str = second_split.next().unwrap().split(' ');
+        let captures = RE
+            .captures(str)
+            .ok_or_else(|| Error::parse("expected a blueprint with four robot costs"))?;
 
-        let ore_ore_cost = ore_cost_str.nth(4).unwrap().parse::<u8>().unwrap();
-        let clay_ore_cost = clay_cost_str.nth(4).unwrap().parse::<u8>().unwrap();
-        let obsidian_ore_cost = obsidian_cost_str.nth(4).unwrap().parse::<u8>().unwrap();
-        let obsidian_clay_cost = obsidian_cost_str.nth(2).unwrap().parse::<u8>().unwrap();
-        let geode_ore_cost = geode_cost_str.nth(4).unwrap().parse::<u8>().unwrap();
-        let geode_obsidian_cost = geode_cost_str.nth(2).unwrap().parse::<u8>().unwrap();
+        let id = captures[1].parse::<u32>()?;
+        let ore_ore_cost = captures[2].parse::<u32>()?;
+        let clay_ore_cost = captures[3].parse::<u32>()?;
+        let obsidian_ore_cost = captures[4].parse::<u32>()?;
+        let obsidian_clay_cost = captures[5].parse::<u32>()?;
+        let geode_ore_cost = captures[6].parse::<u32>()?;
+        let geode_obsidian_cost = captures[7].parse::<u32>()?;
 
-        Blueprint {
+        Ok(Blueprint {
             id,
-            ore_cost: Cost::single(Resource::Ore, ore_ore_cost),
-            clay_cost: Cost::single(Resource::Ore, clay_ore_cost),
-            obsidian_cost: Cost::double(
-                Resource::Ore,
-                obsidian_ore_cost,
-                Resource::Clay,
-                obsidian_clay_cost,
-            ),
-            geode_cost: Cost::double(
-                Resource::Ore,
-                geode_ore_cost,
-                Resource::Obsidian,
-                geode_obsidian_cost,
-            ),
-        }
-    }
-
-    fn costs(&self) -> HashMap<Resource, &Cost> {
-        HashMap::from([
-            (Resource::Ore, &self.ore_cost),
-            (Resource::Clay, &self.clay_cost),
-            (Resource::Obsidian, &self.obsidian_cost),
-            (Resource::Geode, &self.geode_cost),
-        ])
+            costs: [
+                [ore_ore_cost, 0, 0, 0],
+                [clay_ore_cost, 0, 0, 0],
+                [obsidian_ore_cost, obsidian_clay_cost, 0, 0],
+                [geode_ore_cost, 0, geode_obsidian_cost, 0],
+            ],
+        })
     }
+}
 
-    fn max_costs(&self) -> HashMap<Resource, u8> {
-        let mut all_costs: Vec<_> = self
-            .ore_cost
-            .0
-            .iter()
-            .chain(self.clay_cost.0.iter())
-            .chain(self.obsidian_cost.0.iter())
-            .chain(self.geode_cost.0.iter())
-            .collect();
+impl Blueprint {
+    /// The most geodes that can be opened in `time` minutes, starting with a
+    /// single ore robot.
+    fn best_plan(&self, time: u32) -> Plan {
+        let mut max_robots = [u32::MAX; 4];
+        for resource in [Resource::Ore, Resource::Clay, Resource::Obsidian] {
+            let i = resource.index();
+            max_robots[i] = self.costs.iter().map(|c| c[i]).max().unwrap_or(0);
+        }
 
-        all_costs.sort();
-        all_costs.reverse();
-        all_costs.dedup_by_key(|t| t.0);
+        let mut search = PlanSearch {
+            blueprint: self,
+            time,
+            max_robots,
+            seen: HashSet::new(),
+            builds: Vec::new(),
+            best: Plan::default(),
+        };
+        search.explore(State {
+            time_left: time,
+            robots: [1, 0, 0, 0],
+            resources: [0; 4],
+        });
 
-        HashMap::from_iter(
-            all_costs
-                .into_iter()
-                .map(|(k, v)| (k.to_owned(), v.to_owned())),
-        )
+        search.best
     }
+}
 
-    fn simulate(&self, time: u8, start_robots: &HashMap<Resource, i32>) -> u8 {
-        let start_state = State::new(start_robots);
-        let mut states = HashMap::from([(start_state.robots, HashSet::from([start_state]))]);
-        let max_costs = self.max_costs();
-
-        for i in (0..time).rev() {
-            for state in std::mem::take(&mut states).values().flatten() {
-                let mut next_state = state.clone();
-                next_state.process_resources();
-                let mut next_states = vec![next_state];
-
-                for (resource, cost) in self.costs() {
-                    if let Some(max_cost) = max_costs.get(&resource) {
-                        let robot_count = state.robot_count(&resource);
-                        let resource_count = state.resource_count(&resource);
-                        if &robot_count >= max_cost {
-                            continue;
-                        }
-
-                        let max_resources = resource_count + robot_count * i;
-                        let turns = max_resources / max_cost;
-                        if turns >= i {
-                            continue;
-                        }
-                    }
-
-                    let mut next_state = state.clone();
-
-                    let mut has_resources = true;
-
-                    for (res, res_cost) in &cost.0 {
-                        has_resources = next_state.take_resource(res, *res_cost);
-                        if !has_resources {
-                            break;
-                        }
-                    }
+/// The robots built, each with the minute it was started in, and the geodes
+/// they end up opening.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Plan {
+    geodes: u32,
+    builds: Vec<(u32, Resource)>,
+}
 
-                    if has_resources {
-                        next_state.process_resources();
-                        next_state.add_robot(resource);
-                        next_states.push(next_state);
-                    }
-                }
+impl Plan {
+    /// `, building <robot> at <minute>, ...`, or nothing if no robots are
+    /// built.
+    fn build_order(&self) -> String {
+        if self.builds.is_empty() {
+            return String::new();
+        }
+        let builds = self
+            .builds
+            .iter()
+            .map(|(minute, robot)| format!("{} at {}", robot, minute))
+            .join(", ");
+        format!(", building {}", builds)
+    }
+}
 
-                for next_state in next_states {
-                    match states.entry(next_state.robots) {
-                        Entry::Occupied(mut s) => {
-                            s.get_mut().insert(next_state);
-                        }
-                        Entry::Vacant(v) => {
-                            v.insert(HashSet::from([next_state]));
-                        }
-                    }
-                }
-            }
+impl Display for Plan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} geodes{}", self.geodes, self.build_order())
+    }
+}
 
-            let max_geode_robots = states.keys().map(|v| v[3]).max().unwrap_or_default();
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct State {
+    time_left: u32,
+    robots: [u32; 4],
+    resources: [u32; 4],
+}
 
-            states.retain(|r, _| r[3] + 1 >= max_geode_robots);
+impl State {
+    /// The geodes there will be at the end if nothing else is built.
+    fn idle_geodes(&self) -> u32 {
+        let geode = Resource::Geode.index();
+        self.resources[geode] + self.robots[geode] * self.time_left
+    }
 
-            for (_, v) in states.iter_mut() {
-                let max_geodes = v.iter().map(|s| s.resources[3]).max().unwrap_or_default();
-                v.retain(|s| s.resources[3] == max_geodes);
-            }
-        }
+    /// The most geodes there could be at the end, if a geode robot could be
+    /// built every minute from now on.
+    fn upper_bound(&self) -> u32 {
+        let t = self.time_left;
+        self.idle_geodes() + t * t.saturating_sub(1) / 2
+    }
 
-        states
-            .values()
-            .flatten()
-            .map(|s| s.resources[3])
-            .max()
-            .unwrap_or_default()
+    /// The minutes to wait before there is enough to pay `cost`, or `None`
+    /// if there are no robots collecting something it needs.
+    fn wait_for(&self, cost: &[u32; 4]) -> Option<u32> {
+        (0..4)
+            .map(|i| match cost[i].saturating_sub(self.resources[i]) {
+                0 => Some(0),
+                _ if self.robots[i] == 0 => None,
+                missing => Some(missing.div_ceil(self.robots[i])),
+            })
+            .try_fold(0, |wait, w| w.map(|w| wait.max(w)))
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct State {
-    robots: [u8; 4],
-    resources: [u8; 4],
+/// Depth first search over which robot to build next, skipping straight to
+/// the minute it can be afforded, and giving up on any state that could not
+/// beat the best plan so far even if it built a geode robot every minute.
+struct PlanSearch<'a> {
+    blueprint: &'a Blueprint,
+    time: u32,
+    /// No more of each robot than the most that can be spent in a minute.
+    max_robots: [u32; 4],
+    seen: HashSet<State>,
+    builds: Vec<(u32, Resource)>,
+    best: Plan,
 }
 
-impl State {
-    fn new(robots_map: &HashMap<Resource, i32>) -> Self {
-        let ores = robots_map.get(&Resource::Ore).copied().unwrap_or_default();
-        let clay = robots_map.get(&Resource::Clay).copied().unwrap_or_default();
-        let obsidian = robots_map
-            .get(&Resource::Obsidian)
-            .copied()
-            .unwrap_or_default();
-        let geodes = robots_map
-            .get(&Resource::Geode)
-            .copied()
-            .unwrap_or_default();
-
-        let robots = [ores as u8, clay as u8, obsidian as u8, geodes as u8];
-
-        State {
-            robots,
-            resources: [0; 4],
+impl PlanSearch<'_> {
+    fn explore(&mut self, mut state: State) {
+        let geodes = state.idle_geodes();
+        if geodes > self.best.geodes {
+            self.best = Plan {
+                geodes,
+                builds: self.builds.clone(),
+            };
+        }
+        if state.upper_bound() <= self.best.geodes {
+            return;
         }
-    }
 
-    fn index(resoure: &Resource) -> usize {
-        match resoure {
-            Resource::Ore => 0,
-            Resource::Clay => 1,
-            Resource::Obsidian => 2,
-            Resource::Geode => 3,
+        // More than can ever be spent is as good as exactly that much, which
+        // lets the cache catch states that only differ in leftovers.
+        for i in 0..3 {
+            state.resources[i] = state.resources[i].min(self.max_robots[i] * state.time_left);
+        }
+        if !self.seen.insert(state.clone()) {
+            return;
+        }
+
+        for robot in Resource::ALL {
+            let r = robot.index();
+            if state.robots[r] >= self.max_robots[r] {
+                continue;
+            }
+            let cost = &self.blueprint.costs[r];
+            let Some(wait) = state.wait_for(cost) else {
+                continue;
+            };
+            // A robot finished in the last minute never collects anything.
+            if wait + 1 >= state.time_left {
+                continue;
+            }
+
+            let mut next = State {
+                time_left: state.time_left - wait - 1,
+                ..state.clone()
+            };
+            for ((have, robots), cost) in next.resources.iter_mut().zip(state.robots).zip(cost) {
+                *have = *have + robots * (wait + 1) - cost;
+            }
+            next.robots[r] += 1;
+
+            let minute = self.time - state.time_left + wait + 1;
+            self.builds.push((minute, robot));
+            self.explore(next);
+            self.builds.pop();
         }
     }
+}
 
-    fn robot_count(&self, resource: &Resource) -> u8 {
-        self.robots[Self::index(resource)]
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::{best_plans, Blueprint, Day19, Plan};
+    use crate::{
+        params::Params,
+        solution::{example, example_with, Solution},
+    };
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day19, include_str!("examples/day19.txt")),
+            ("33".to_string(), "3472".to_string())
+        );
+
+        let mut params = Params::new();
+        params.insert("plans", "true");
+        assert_eq!(
+            example_with(Day19, include_str!("examples/day19.txt"), &params),
+            ("33".to_string(), "3472".to_string())
+        );
     }
 
-    fn resource_count(&self, resource: &Resource) -> u8 {
-        self.resources[Self::index(resource)]
+    #[test]
+    fn details() {
+        let lines: Vec<_> = include_str!("examples/day19.txt")
+            .lines()
+            .map(String::from)
+            .collect();
+
+        let parsed = Day19.parse(lines.clone()).unwrap();
+        assert_eq!(
+            Day19.details(&parsed).unwrap(),
+            [
+                "Blueprint 1 (24 minutes): 9 geodes, quality level 9",
+                "Blueprint 2 (24 minutes): 12 geodes, quality level 24",
+            ]
+        );
+
+        let mut params = Params::new();
+        params.insert("plans", "true");
+        let parsed = Day19.parse_with(lines, &params).unwrap();
+        let details = Day19.details(&parsed).unwrap();
+        assert_eq!(details.len(), 4);
+        assert_eq!(
+            details[0],
+            "Blueprint 1 (24 minutes): 9 geodes, quality level 9, building clay at 3, \
+             clay at 5, clay at 7, obsidian at 11, clay at 12, obsidian at 15, \
+             geode at 18, geode at 21"
+        );
+        assert!(details[2].starts_with("Blueprint 1 (32 minutes): 56 geodes, building "));
     }
 
-    fn take_resource(&mut self, resoure: &Resource, amount: u8) -> bool {
-        let i = State::index(resoure);
-        let count = self.resources[i];
-        if count >= amount {
-            self.resources[i] -= amount;
-            true
-        } else {
-            false
+    /// Follows `plan` minute by minute, checking every robot is affordable
+    /// when it is started, and returns the geodes opened.
+    fn replay(blueprint: &Blueprint, plan: &Plan, time: u32) -> u32 {
+        let mut robots = [1, 0, 0, 0];
+        let mut resources = [0; 4];
+        let mut builds = plan.builds.iter().peekable();
+
+        for minute in 1..=time {
+            let build = builds
+                .next_if(|(m, _)| *m == minute)
+                .map(|(_, r)| r.index());
+            if let Some(r) = build {
+                for (have, cost) in resources.iter_mut().zip(blueprint.costs[r]) {
+                    assert!(*have >= cost, "cannot afford robot {} at {}", r, minute);
+                    *have -= cost;
+                }
+            }
+            for (have, robots) in resources.iter_mut().zip(robots) {
+                *have += robots;
+            }
+            if let Some(r) = build {
+                robots[r] += 1;
+            }
         }
-    }
 
-    fn add_robot(&mut self, resoure: Resource) {
-        let i = State::index(&resoure);
-        self.robots[i] += 1;
+        assert_eq!(builds.next(), None);
+        resources[3]
     }
 
-    fn process_resources(&mut self) {
-        for (i, a) in self.robots.iter().enumerate() {
-            self.resources[i] += a;
+    #[test]
+    fn plans() {
+        let blueprints: Vec<_> = include_str!("examples/day19.txt")
+            .lines()
+            .map(|l| Blueprint::from_str(l).unwrap())
+            .collect();
+
+        for (blueprint, time, geodes) in [
+            (&blueprints[0], 24, 9),
+            (&blueprints[1], 24, 12),
+            (&blueprints[0], 32, 56),
+            (&blueprints[1], 32, 62),
+        ] {
+            let plan = blueprint.best_plan(time);
+            assert_eq!(plan.geodes, geodes);
+            assert_eq!(replay(blueprint, &plan, time), geodes);
         }
+
+        let plan = blueprints[0].best_plan(24);
+        assert_eq!(
+            plan.to_string(),
+            "9 geodes, building clay at 3, clay at 5, clay at 7, obsidian at 11, \
+             clay at 12, obsidian at 15, geode at 18, geode at 21"
+        );
+        assert_eq!(blueprints[0].best_plan(5).to_string(), "0 geodes");
+
+        // Worked out in parallel but reported in id order, whichever order
+        // the blueprints come in.
+        let reversed: Vec<_> = blueprints.iter().rev().cloned().collect();
+        let expected: Vec<_> = blueprints.iter().map(|b| (b.id, b.best_plan(24))).collect();
+        assert_eq!(best_plans(&reversed, 24), expected);
+        assert_eq!(best_plans(&[], 24), []);
     }
 }
@@ -1,5 +1,10 @@
 use std::str::FromStr;
 
+use crate::{
+    error::{parse_lines, Error, Result},
+    solution::Solution,
+};
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 enum Rps {
     Rock,
@@ -15,34 +20,40 @@ enum Outcome {
 }
 
 #[derive(Debug)]
-struct Round {
+pub struct Round {
     opponent: Rps,
     you: Rps,
 }
 
 #[derive(Debug)]
-struct OutcomeRound {
+pub struct OutcomeRound {
     opponent: Rps,
     outcome: Outcome,
 }
 
+fn split_round(str: &str) -> Result<(&str, &str)> {
+    str.split_once(' ')
+        .ok_or_else(|| Error::parse("expected two letters separated by a space"))
+}
+
 impl FromStr for Round {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(str: &str) -> Result<Self, Self::Err> {
-        let opponent = match &str[0..1] {
+        let (opponent, you) = split_round(str)?;
+        let opponent = match opponent {
             "A" => Ok(Rps::Rock),
             "B" => Ok(Rps::Paper),
             "C" => Ok(Rps::Scissors),
 
-            other => Err(format!("Unknown letter {other}")),
+            other => Err(Error::parse(format!("unknown letter {other}"))),
         }?;
-        let you = match &str[2..3] {
+        let you = match you {
             "X" => Ok(Rps::Rock),
             "Y" => Ok(Rps::Paper),
             "Z" => Ok(Rps::Scissors),
 
-            other => Err(format!("Unknown letter {other}")),
+            other => Err(Error::parse(format!("unknown letter {other}"))),
         }?;
 
         Ok(Round { opponent, you })
@@ -50,22 +61,23 @@ impl FromStr for Round {
 }
 
 impl FromStr for OutcomeRound {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(str: &str) -> Result<Self, Self::Err> {
-        let opponent = match &str[0..1] {
+        let (opponent, you) = split_round(str)?;
+        let opponent = match opponent {
             "A" => Ok(Rps::Rock),
             "B" => Ok(Rps::Paper),
             "C" => Ok(Rps::Scissors),
 
-            other => Err(format!("Unknown letter {other}")),
+            other => Err(Error::parse(format!("unknown letter {other}"))),
         }?;
-        let you = match &str[2..3] {
+        let you = match you {
             "X" => Ok(Outcome::Lose),
             "Y" => Ok(Outcome::Draw),
             "Z" => Ok(Outcome::Win),
 
-            other => Err(format!("Unknown letter {other}")),
+            other => Err(Error::parse(format!("unknown letter {other}"))),
         }?;
 
         Ok(OutcomeRound {
@@ -126,23 +138,44 @@ impl OutcomeRound {
     }
 }
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let parsed: Result<Vec<Round>, _> = lines.iter().map(|s| Round::from_str(s)).collect();
+pub struct Day2;
 
-    let part1: i32 = parsed?.iter().map(|r| r.score()).sum();
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+    type Parsed = (Vec<Round>, Vec<OutcomeRound>);
+    type Part1 = i32;
+    type Part2 = i32;
 
-    println!("Part 1 {}", part1);
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        let rounds = parse_lines(&lines, Round::from_str)?;
+        let outcome_rounds = parse_lines(&lines, OutcomeRound::from_str)?;
+
+        Ok((rounds, outcome_rounds))
+    }
 
-    let parsed: Result<Vec<OutcomeRound>, _> =
-        lines.iter().map(|s| OutcomeRound::from_str(s)).collect();
+    fn part1(&self, (rounds, _): &Self::Parsed) -> Result<i32> {
+        Ok(rounds.iter().map(|r| r.score()).sum())
+    }
 
-    let part2: i32 = parsed?
-        .iter()
-        .map(|r| r.to_round())
-        .map(|r| r.score())
-        .sum();
+    fn part2(&self, (_, outcome_rounds): &Self::Parsed) -> Result<i32> {
+        Ok(outcome_rounds
+            .iter()
+            .map(|r| r.to_round())
+            .map(|r| r.score())
+            .sum())
+    }
+}
 
-    println!("Part 2 {}", part2);
+#[cfg(test)]
+mod test {
+    use super::Day2;
+    use crate::solution::example;
 
-    Ok(())
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day2, include_str!("examples/day2.txt")),
+            ("15".to_string(), "12".to_string())
+        );
+    }
 }
@@ -1,37 +1,53 @@
 use std::{cmp::Ordering, collections::VecDeque};
 
+use crate::{
+    error::{parse_lines, Error, Result},
+    solution::Solution,
+};
+
 const ENCRYPTION_KEY: i64 = 811589153;
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let numbers = lines
-        .iter()
-        .map(|s| s.parse::<i64>())
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?;
+pub struct Day20;
 
-    let mut encrypted: VecDeque<_> = numbers.iter().copied().enumerate().collect();
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+    type Parsed = Vec<i64>;
+    type Part1 = i64;
+    type Part2 = i64;
 
-    decrypt(&mut encrypted);
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        parse_lines(&lines, |l| Ok(l.parse::<i64>()?))
+    }
 
-    println!("Part 1 {}", sum_coords(encrypted.make_contiguous()));
+    fn part1(&self, numbers: &Self::Parsed) -> Result<i64> {
+        let mut encrypted: VecDeque<_> = numbers.iter().copied().enumerate().collect();
 
-    let mut real_encrypted: VecDeque<_> = numbers
-        .iter()
-        .copied()
-        .map(|v| v * ENCRYPTION_KEY)
-        .enumerate()
-        .collect();
+        decrypt(&mut encrypted);
 
-    for _ in 0..10 {
-        decrypt(&mut real_encrypted);
+        sum_coords(encrypted.make_contiguous())
     }
 
-    println!("Part 2 {}", sum_coords(real_encrypted.make_contiguous()));
+    fn part2(&self, numbers: &Self::Parsed) -> Result<i64> {
+        let mut real_encrypted: VecDeque<_> = numbers
+            .iter()
+            .copied()
+            .map(|v| v * ENCRYPTION_KEY)
+            .enumerate()
+            .collect();
+
+        for _ in 0..10 {
+            decrypt(&mut real_encrypted);
+        }
 
-    Ok(())
+        sum_coords(real_encrypted.make_contiguous())
+    }
 }
 
 fn decrypt(encrypted: &mut VecDeque<(usize, i64)>) {
+    if encrypted.len() < 2 {
+        return;
+    }
+
     for i in 0..encrypted.len() {
         let current_i = encrypted.iter().position(|(j, _)| i == *j).unwrap();
         let current = encrypted.remove(current_i).unwrap();
@@ -53,12 +69,57 @@ fn decrypt(encrypted: &mut VecDeque<(usize, i64)>) {
     }
 }
 
-fn sum_coords(decrypted: &[(usize, i64)]) -> i64 {
-    let index = decrypted.iter().position(|(_, v)| *v == 0).unwrap();
+fn sum_coords(decrypted: &[(usize, i64)]) -> Result<i64> {
+    let index = decrypted
+        .iter()
+        .position(|(_, v)| *v == 0)
+        .ok_or_else(|| Error::missing_section("number 0"))?;
 
     let first = decrypted[(index + 1000) % decrypted.len()].1;
     let second = decrypted[(index + 2000) % decrypted.len()].1;
     let third = decrypted[(index + 3000) % decrypted.len()].1;
 
-    first + second + third
+    Ok(first + second + third)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+
+    use super::{decrypt, Day20};
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day20, include_str!("examples/day20.txt")),
+            ("3".to_string(), "1623178306".to_string())
+        );
+    }
+
+    /// Mixes `numbers` once, returning them in order starting from 0.
+    fn mix(numbers: &[i64]) -> Vec<i64> {
+        let mut encrypted: VecDeque<_> = numbers.iter().copied().enumerate().collect();
+        decrypt(&mut encrypted);
+
+        let zero = encrypted.iter().position(|(_, v)| *v == 0).unwrap();
+        encrypted.rotate_left(zero);
+        encrypted.into_iter().map(|(_, v)| v).collect()
+    }
+
+    #[test]
+    fn decrypt_wraps() {
+        assert_eq!(mix(&[1, 2, -3, 3, -2, 0, 4]), [0, 3, -2, 1, 2, -3, 4]);
+
+        // Moving past every other number lands back where it started.
+        assert_eq!(mix(&[0, 2, 4]), [0, 2, 4]);
+        assert_eq!(mix(&[0, -3, 6, 3]), [0, -3, 6, 3]);
+
+        // Large moves wrap around more than once.
+        assert_eq!(mix(&[0, -4, 5, 6]), [0, 6, -4, 5]);
+        assert_eq!(mix(&[0, 7, 1]), [0, 7, 1]);
+        assert_eq!(mix(&[0, 4, 1]), [0, 1, 4]);
+        assert_eq!(mix(&[0, -7, 1]), [0, -7, 1]);
+        assert_eq!(mix(&[0, 1_000_000_000_001, 1]), [0, 1_000_000_000_001, 1]);
+    }
 }
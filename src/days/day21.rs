@@ -1,128 +1,403 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let monkeys = lines
-        .iter()
-        .map(|l| parse(l))
-        .collect::<Result<HashMap<_, _>, _>>()?;
+use crate::{
+    error::{parse_lines, Error, Result},
+    solution::{Export, Solution},
+};
 
-    let results = get_results(monkeys.iter().collect());
+pub struct Day21;
 
-    println!("Part 1: {}", results.get("root").unwrap());
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+    type Parsed = HashMap<String, Monkey>;
+    type Part1 = i64;
+    type Part2 = i64;
 
-    let human = "humn".to_string();
-    let start = 0.0;
-    let mut human_val = start;
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        let parsed = parse_lines(&lines, parse)?;
+        let mut names = HashSet::new();
+        for (i, (name, _)) in parsed.iter().enumerate() {
+            if !names.insert(name.as_str()) {
+                return Err(
+                    Error::invalid_value(format!("more than one monkey named {}", name))
+                        .at_line(i, &lines[i]),
+                );
+            }
+        }
 
-    let mut values = Vec::new();
-    let mut delta = 0.0;
-    loop {
-        let mut monkeys: HashMap<_, _> = monkeys.iter().collect();
-        let root = monkeys.remove(&"root".to_owned());
-        if let Some(Monkey::Op(m1, _, m2)) = root {
-            let value = Monkey::Val(human_val);
-            monkeys.insert(&human, &value);
-            let results = get_results(monkeys);
-            delta = results.get(m2.as_str()).unwrap() - results.get(m1.as_str()).unwrap();
+        for (i, (_, monkey)) in parsed.iter().enumerate() {
+            if let Monkey::Op(m1, _, m2) = monkey {
+                if let Some(missing) = [m1, m2].into_iter().find(|m| !names.contains(m.as_str())) {
+                    return Err(Error::invalid_value(format!("no monkey named {}", missing))
+                        .at_line(i, &lines[i]));
+                }
+            }
+        }
 
-            values.push(*results.get(m1.as_str()).unwrap());
+        Ok(parsed.into_iter().collect())
+    }
+
+    fn part1(&self, monkeys: &Self::Parsed) -> Result<i64> {
+        if !monkeys.contains_key(ROOT) {
+            return Err(Error::missing_section("root monkey"));
         }
-        human_val += 1.0;
-        if human_val > start + 100.0 {
-            break;
+
+        evaluate(monkeys, ROOT)
+    }
+
+    fn part2(&self, monkeys: &Self::Parsed) -> Result<i64> {
+        if !monkeys.contains_key(HUMAN) {
+            return Err(Error::missing_section("humn monkey"));
         }
+        let (left, right) = match monkeys.get(ROOT) {
+            Some(Monkey::Op(m1, _, m2)) => {
+                // Checks there are no cycles for expr to get lost in.
+                topological_order(monkeys, ROOT)?;
+                (expr(monkeys, m1)?, expr(monkeys, m2)?)
+            }
+            Some(Monkey::Val(_)) => {
+                return Err(Error::invalid_value("root monkey must compare two monkeys"))
+            }
+            None => return Err(Error::missing_section("root monkey")),
+        };
+
+        let human = match (left, right) {
+            (Expr::Num(target), unknown) | (unknown, Expr::Num(target)) => solve(&unknown, target)?,
+            _ => return Err(Error::unsolvable("humn appears on both sides of root")),
+        };
+
+        human
+            .to_integer()
+            .ok_or_else(|| Error::unsolvable(format!("no integer solution, humn = {}", human)))
     }
 
-    let gradients: Vec<_> = values.windows(2).map(|w| w[1] - w[0]).collect();
-    let avg = gradients.iter().sum::<f64>() / gradients.len() as f64;
+    fn export(&self, monkeys: &Self::Parsed, format: Export) -> Result<Option<String>> {
+        match format {
+            Export::Infix => {
+                let (m1, m2) = match monkeys.get(ROOT) {
+                    Some(Monkey::Op(m1, _, m2)) => (m1, m2),
+                    _ => return Err(Error::missing_section("root monkey")),
+                };
+                topological_order(monkeys, ROOT)?;
 
-    loop {
-        if delta == 0.0 {
-            break;
+                Ok(Some(format!(
+                    "{} = {}",
+                    expr(monkeys, m1)?,
+                    expr(monkeys, m2)?
+                )))
+            }
+            Export::Dot => Ok(Some(dot(monkeys))),
+        }
+    }
+}
+
+const ROOT: &str = "root";
+const HUMAN: &str = "humn";
+
+/// A monkey's job with the names resolved and everything that does not
+/// depend on `humn` worked out.
+enum Expr {
+    Num(Ratio),
+    Human,
+    Op(Box<Expr>, Op, Box<Expr>),
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Num(n) => write!(f, "{}", n),
+            Expr::Human => write!(f, "{}", HUMAN),
+            Expr::Op(a, op, b) => write!(f, "({} {} {})", a, op, b),
+        }
+    }
+}
+
+fn expr(monkeys: &HashMap<String, Monkey>, name: &str) -> Result<Expr> {
+    if name == HUMAN {
+        return Ok(Expr::Human);
+    }
+
+    match &monkeys[name] {
+        Monkey::Val(v) => Ok(Expr::Num(Ratio::from(*v))),
+        Monkey::Op(m1, op, m2) => match (expr(monkeys, m1)?, expr(monkeys, m2)?) {
+            (Expr::Num(a), Expr::Num(b)) => Ok(Expr::Num(a.apply(op, b)?)),
+            (a, b) => Ok(Expr::Op(Box::new(a), op.clone(), Box::new(b))),
+        },
+    }
+}
+
+/// Finds the value of `humn` that makes `expr` equal `target`, undoing one
+/// operation at a time on the way down to it.
+fn solve(expr: &Expr, target: Ratio) -> Result<Ratio> {
+    let (unknown, target) = match expr {
+        Expr::Human => return Ok(target),
+        Expr::Num(n) if *n == target => {
+            return Err(Error::unsolvable("root is equal whatever humn yells"))
+        }
+        Expr::Num(_) => return Err(Error::unsolvable("root can never be equal")),
+        Expr::Op(a, op, b) => match (a.as_ref(), op, b.as_ref()) {
+            (Expr::Num(c), Op::Add, x) | (x, Op::Add, Expr::Num(c)) => {
+                (x, target.apply(&Op::Sub, *c)?)
+            }
+            (Expr::Num(c), Op::Mul, x) | (x, Op::Mul, Expr::Num(c)) => {
+                if c.is_zero() {
+                    return solve(&Expr::Num(Ratio::from(0)), target);
+                }
+                (x, target.apply(&Op::Div, *c)?)
+            }
+            (Expr::Num(c), Op::Sub, x) => (x, c.apply(&Op::Sub, target)?),
+            (x, Op::Sub, Expr::Num(c)) => (x, target.apply(&Op::Add, *c)?),
+            (Expr::Num(c), Op::Div, x) => {
+                if target.is_zero() {
+                    return solve(&Expr::Num(*c), target);
+                }
+                (x, c.apply(&Op::Div, target)?)
+            }
+            (x, Op::Div, Expr::Num(c)) => {
+                if c.is_zero() {
+                    return Err(Error::unsolvable("division by zero"));
+                }
+                (x, target.apply(&Op::Mul, *c)?)
+            }
+            _ => return Err(Error::unsolvable("humn appears more than once")),
+        },
+    };
+
+    solve(unknown, target)
+}
+
+/// An exact fraction, kept in lowest terms with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Ratio {
+    num: i128,
+    den: i128,
+}
+
+impl Ratio {
+    fn new(num: i128, den: i128) -> Result<Ratio> {
+        if den == 0 {
+            return Err(Error::unsolvable("division by zero"));
+        }
+
+        let divisor = gcd(num, den) * den.signum();
+        Ok(Ratio {
+            num: num / divisor,
+            den: den / divisor,
+        })
+    }
+
+    fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    fn to_integer(self) -> Option<i64> {
+        if self.den == 1 {
+            self.num.try_into().ok()
         } else {
-            let difference = delta / avg;
-            human_val += difference;
+            None
         }
+    }
+
+    fn apply(self, op: &Op, other: Ratio) -> Result<Ratio> {
+        let (a, b, c, d) = (self.num, self.den, other.num, other.den);
+        let overflow = || Error::unsolvable("arithmetic overflowed");
+        let mul = |x: i128, y: i128| x.checked_mul(y).ok_or_else(overflow);
 
-        let mut monkeys: HashMap<_, _> = monkeys.iter().collect();
-        let root = monkeys.remove(&"root".to_owned());
-        if let Some(Monkey::Op(m1, _, m2)) = root {
-            let value = Monkey::Val(human_val);
-            monkeys.insert(&human, &value);
-            let results = get_results(monkeys);
-            delta = results.get(m2.as_str()).unwrap() - results.get(m1.as_str()).unwrap();
+        let (num, den) = match op {
+            Op::Add => (mul(a, d)?.checked_add(mul(c, b)?), mul(b, d)?),
+            Op::Sub => (mul(a, d)?.checked_sub(mul(c, b)?), mul(b, d)?),
+            Op::Mul => (Some(mul(a, c)?), mul(b, d)?),
+            Op::Div => (Some(mul(a, d)?), mul(b, c)?),
+        };
 
-            values.push(*results.get(m1.as_str()).unwrap());
+        Ratio::new(num.ok_or_else(overflow)?, den)
+    }
+}
+
+impl From<i64> for Ratio {
+    fn from(n: i64) -> Self {
+        Ratio {
+            num: n.into(),
+            den: 1,
         }
     }
+}
 
-    println!("Part 2: {}", human_val);
+impl Display for Ratio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
 
-    Ok(())
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
 }
 
-fn get_results<'a>(monkeys: HashMap<&'a String, &Monkey>) -> HashMap<&'a str, f64> {
-    let mut remaining = monkeys;
-    let mut results: HashMap<&str, f64> = HashMap::new();
+/// Every monkey as a Graphviz node, with an edge to each monkey it waits on.
+/// Node IDs are quoted so names like `node` or `graph` are not read as DOT
+/// keywords.
+fn dot(monkeys: &HashMap<String, Monkey>) -> String {
+    let mut names: Vec<_> = monkeys.keys().collect();
+    names.sort();
 
-    loop {
-        if remaining.is_empty() {
-            break;
+    let mut dot = vec!["digraph monkeys {".to_string()];
+    for name in names {
+        let style = if name == HUMAN || name == ROOT {
+            ", style=filled"
+        } else {
+            ""
+        };
+        match &monkeys[name] {
+            Monkey::Val(v) => dot.push(format!(
+                "    \"{}\" [label=\"{}: {}\"{}];",
+                name, name, v, style
+            )),
+            Monkey::Op(m1, op, m2) => {
+                dot.push(format!(
+                    "    \"{}\" [label=\"{}: {}\"{}];",
+                    name, name, op, style
+                ));
+                dot.push(format!("    \"{}\" -> \"{}\";", name, m1));
+                dot.push(format!("    \"{}\" -> \"{}\";", name, m2));
+            }
         }
+    }
+    dot.push("}".to_string());
 
-        let keys: Vec<_> = remaining.keys().copied().collect();
-        for m in keys {
-            let monkey = remaining[m];
+    dot.join("\n")
+}
 
-            match monkey {
-                Monkey::Op(m1, op, m2) => {
-                    if let Some(v1) = results.get(m1.as_str()) {
-                        if let Some(v2) = results.get(m2.as_str()) {
-                            let v = match op {
-                                Op::Add => v1 + v2,
-                                Op::Sub => v1 - v2,
-                                Op::Mul => v1 * v2,
-                                Op::Div => v1 / v2,
-                            };
-                            results.insert(m, v);
-                            remaining.remove(m);
-                        }
-                    };
-                }
+/// The monkeys `name` waits on, directly or not, each after every monkey it
+/// waits on and ending with `name` itself.
+fn topological_order<'a>(
+    monkeys: &'a HashMap<String, Monkey>,
+    name: &'a str,
+) -> Result<Vec<&'a str>> {
+    let mut order = Vec::new();
+    let mut done = HashSet::new();
+    let mut waiting: Vec<&str> = Vec::new();
+    let mut stack = vec![(name, false)];
 
-                Monkey::Val(v) => {
-                    results.insert(m, *v);
-                    remaining.remove(m);
-                }
-            };
+    while let Some((name, finished)) = stack.pop() {
+        if finished {
+            waiting.pop();
+            done.insert(name);
+            order.push(name);
+            continue;
+        }
+        if done.contains(name) {
+            continue;
+        }
+        if let Some(i) = waiting.iter().position(|w| *w == name) {
+            let mut cycle = waiting[i..].to_vec();
+            cycle.push(name);
+            return Err(Error::invalid_value(format!(
+                "monkeys wait on each other: {}",
+                cycle.join(" -> ")
+            )));
+        }
+
+        let monkey = monkeys
+            .get(name)
+            .ok_or_else(|| Error::invalid_value(format!("no monkey named {}", name)))?;
+        waiting.push(name);
+        stack.push((name, true));
+        if let Monkey::Op(m1, _, m2) = monkey {
+            stack.push((m2, false));
+            stack.push((m1, false));
         }
     }
-    results
+
+    Ok(order)
+}
+
+/// What `name` yells, working out each monkey it waits on once.
+fn evaluate(monkeys: &HashMap<String, Monkey>, name: &str) -> Result<i64> {
+    let mut values = HashMap::new();
+
+    for name in topological_order(monkeys, name)? {
+        let value = match &monkeys[name] {
+            Monkey::Val(v) => *v,
+            Monkey::Op(m1, op, m2) => {
+                let (a, b) = (values[m1.as_str()], values[m2.as_str()]);
+                op.apply(a, b).ok_or_else(|| {
+                    Error::unsolvable(format!("{} cannot work out {} {} {}", name, a, op, b))
+                })?
+            }
+        };
+        values.insert(name, value);
+    }
+
+    Ok(values[name])
 }
 
-enum Op {
+#[derive(Clone)]
+pub enum Op {
     Add,
     Sub,
     Mul,
     Div,
 }
 
-enum Monkey {
+impl Op {
+    /// The result, or `None` if it overflows, divides by zero or leaves a
+    /// remainder.
+    fn apply(&self, a: i64, b: i64) -> Option<i64> {
+        match self {
+            Op::Add => a.checked_add(b),
+            Op::Sub => a.checked_sub(b),
+            Op::Mul => a.checked_mul(b),
+            Op::Div => a.checked_div(b).filter(|_| a % b == 0),
+        }
+    }
+}
+
+impl Display for Op {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            Op::Add => "+",
+            Op::Sub => "-",
+            Op::Mul => "*",
+            Op::Div => "/",
+        };
+        write!(f, "{}", op)
+    }
+}
+
+pub enum Monkey {
     Op(String, Op, String),
-    Val(f64),
+    Val(i64),
 }
 
-fn parse(str: &str) -> Result<(String, Monkey), String> {
+pub fn parse(str: &str) -> Result<(String, Monkey)> {
     let mut split = str.split(": ");
 
-    let name = split.next().ok_or_else(|| "no name".to_owned())?.to_owned();
+    let name = split
+        .next()
+        .ok_or_else(|| Error::parse("no name"))?
+        .to_owned();
 
-    let op = split.next().ok_or_else(|| "no op".to_owned())?;
+    let op = split
+        .next()
+        .ok_or_else(|| Error::parse("expected \"<name>: <job>\""))?;
 
-    let parsed_monkey = op.parse::<f64>().map(Monkey::Val).or_else(|_| {
+    let parsed_monkey = op.parse::<i64>().map(Monkey::Val).or_else(|_| {
         let split = op.split(' ').collect::<Vec<_>>();
         if split.len() != 3 {
-            Err("Wrong input length".to_owned())
+            Err(Error::parse(
+                "expected a number or \"<monkey> <op> <monkey>\"",
+            ))
         } else {
             let m1 = split[0].to_owned();
             let m2 = split[2].to_owned();
@@ -132,7 +407,7 @@ fn parse(str: &str) -> Result<(String, Monkey), String> {
                 "-" => Ok(Op::Sub),
                 "*" => Ok(Op::Mul),
                 "/" => Ok(Op::Div),
-                _ => Err("Unrecognised op".to_owned()),
+                other => Err(Error::parse(format!("unrecognised op {}", other))),
             };
 
             op.map(|o| Monkey::Op(m1, o, m2))
@@ -141,3 +416,149 @@ fn parse(str: &str) -> Result<(String, Monkey), String> {
 
     parsed_monkey.map(|o| (name, o))
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{evaluate, parse, topological_order, Day21, Monkey};
+    use crate::solution::{example, Export, Solution};
+
+    fn parse_all(input: &[&str]) -> HashMap<String, Monkey> {
+        input.iter().map(|l| parse(l).unwrap()).collect()
+    }
+
+    fn part2(input: &[&str]) -> String {
+        let lines = input.iter().map(|l| l.to_string()).collect();
+        match Day21.parse(lines).and_then(|parsed| Day21.part2(&parsed)) {
+            Ok(human) => human.to_string(),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day21, include_str!("examples/day21.txt")),
+            ("152".to_string(), "301".to_string())
+        );
+    }
+
+    #[test]
+    fn solves_for_humn() {
+        // humn in the divisor: 60 / humn = 4.
+        assert_eq!(
+            part2(&["root: a + b", "a: c / humn", "b: 4", "c: 60", "humn: 1"]),
+            "15"
+        );
+        // Far beyond what an f64 holds exactly.
+        assert_eq!(
+            part2(&[
+                "root: a + b",
+                "a: humn * c",
+                "b: 9007199254740993",
+                "c: 1",
+                "humn: 1"
+            ]),
+            "9007199254740993"
+        );
+        assert_eq!(
+            part2(&["root: a + b", "a: humn * c", "b: 7", "c: 2", "humn: 1"]),
+            "unsolvable input: no integer solution, humn = 7/2"
+        );
+        assert_eq!(
+            part2(&["root: a + b", "a: humn * c", "b: 0", "c: 0", "humn: 1"]),
+            "unsolvable input: root is equal whatever humn yells"
+        );
+        assert_eq!(
+            part2(&["root: a + b", "a: humn / c", "b: 0", "c: 0", "humn: 1"]),
+            "unsolvable input: division by zero"
+        );
+        assert_eq!(
+            part2(&["root: a + b", "a: humn * humn", "b: 4", "humn: 1"]),
+            "unsolvable input: humn appears more than once"
+        );
+    }
+
+    #[test]
+    fn evaluates_in_order() {
+        let monkeys = parse_all(&["root: a * b", "a: b + c", "b: 2", "c: 3"]);
+        assert_eq!(
+            topological_order(&monkeys, "root").unwrap(),
+            ["b", "c", "a", "root"]
+        );
+        assert_eq!(evaluate(&monkeys, "root"), Ok(10));
+
+        let monkeys = parse_all(&["root: a + b", "a: b * c", "b: 2", "c: root / b"]);
+        assert_eq!(
+            evaluate(&monkeys, "root").unwrap_err().to_string(),
+            "invalid value: monkeys wait on each other: root -> a -> c -> root"
+        );
+
+        let monkeys = parse_all(&["root: a + b", "a: 1"]);
+        assert_eq!(
+            evaluate(&monkeys, "root").unwrap_err().to_string(),
+            "invalid value: no monkey named b"
+        );
+
+        let monkeys = parse_all(&["root: a * a", "a: 9999999999"]);
+        assert_eq!(
+            evaluate(&monkeys, "root").unwrap_err().to_string(),
+            "unsolvable input: root cannot work out 9999999999 * 9999999999"
+        );
+
+        let monkeys = parse_all(&["root: a / b", "a: 7", "b: 2"]);
+        assert_eq!(
+            evaluate(&monkeys, "root").unwrap_err().to_string(),
+            "unsolvable input: root cannot work out 7 / 2"
+        );
+
+        let lines = ["root: a + b", "a: 1", "b: 2", "a: 3"].map(String::from);
+        assert_eq!(
+            Day21.parse(lines.to_vec()).err().map(|e| e.to_string()),
+            Some("input line 4: invalid value: more than one monkey named a\n    a: 3".to_string())
+        );
+    }
+
+    #[test]
+    fn exports() {
+        let export = |input: &str, format| {
+            let lines = input.lines().map(String::from).collect();
+            Day21
+                .export(&Day21.parse(lines).unwrap(), format)
+                .unwrap()
+                .unwrap()
+        };
+
+        assert_eq!(
+            export(include_str!("examples/day21.txt"), Export::Infix),
+            "((4 + (2 * (humn - 3))) / 4) = 150"
+        );
+        assert_eq!(
+            export("root: a / humn\na: 3\nhumn: 5", Export::Dot),
+            [
+                "digraph monkeys {",
+                "    \"a\" [label=\"a: 3\"];",
+                "    \"humn\" [label=\"humn: 5\", style=filled];",
+                "    \"root\" [label=\"root: /\", style=filled];",
+                "    \"root\" -> \"a\";",
+                "    \"root\" -> \"humn\";",
+                "}",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            export("root: node + edge\nnode: 1\nedge: 2", Export::Dot),
+            [
+                "digraph monkeys {",
+                "    \"edge\" [label=\"edge: 2\"];",
+                "    \"node\" [label=\"node: 1\"];",
+                "    \"root\" [label=\"root: +\", style=filled];",
+                "    \"root\" -> \"node\";",
+                "    \"root\" -> \"edge\";",
+                "}",
+            ]
+            .join("\n")
+        );
+    }
+}
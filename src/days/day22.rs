@@ -1,45 +1,53 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    ops::{Add, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+use super::{grid::Grid, point::Point2d};
+use crate::{
+    error::{Error, Result},
+    solution::Solution,
+};
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+    type Parsed = (Map, Path);
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        let split: Vec<_> = lines.split(|l| l.is_empty()).collect();
+        match split.len() {
+            1 => return Err(Error::missing_section("path")),
+            2 => (),
+            _ => {
+                return Err(Error::parse(
+                    "expected a map and a path separated by one blank line",
+                ))
+            }
+        }
 
-use super::point::Point2d;
+        let map = Map::from_strs(split[0])?;
+        let path_line = split[0].len() + 1;
+        let path = Path::from_str(&split[1].concat())
+            .map_err(|e| e.at_line(path_line, &lines[path_line]))?;
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let split: Vec<_> = lines.split(|l| l.is_empty()).collect();
-    if split.len() != 2 {
-        return Err("bad input".to_owned());
+        Ok((map, path))
     }
 
-    let mut map = Map::from_strs(split[0]);
-    let path = Path::from_str(&split[1].concat());
-
-    let Monkey(p, o) = path.traverse(&map);
-
-    let orient_score = match o {
-        Orient::Right => 0,
-        Orient::Down => 1,
-        Orient::Left => 2,
-        Orient::Up => 3,
-    };
-
-    let part1 = 1000 * p.y() + 4 * p.x() + orient_score;
-
-    println!("Part 1: {}", part1);
-
-    map.make_cube();
-
-    let Monkey(p, o) = path.traverse(&map);
-
-    let orient_score = match o {
-        Orient::Right => 0,
-        Orient::Down => 1,
-        Orient::Left => 2,
-        Orient::Up => 3,
-    };
-
-    let part1 = 1000 * p.y() + 4 * p.x() + orient_score;
+    fn part1(&self, (map, path): &Self::Parsed) -> Result<i32> {
+        Ok(path.traverse(map)?.password())
+    }
 
-    println!("Part 2: {}", part1);
+    fn part2(&self, (map, path): &Self::Parsed) -> Result<i32> {
+        let mut map = map.clone();
+        map.make_cube()?;
 
-    Ok(())
+        Ok(path.traverse(&map)?.password())
+    }
 }
 
 #[derive(Debug)]
@@ -52,13 +60,15 @@ enum Rot {
 struct Step(Rot, u8);
 
 #[derive(Debug)]
-struct Path {
+pub struct Path {
     forward: u8,
     rest: Vec<Step>,
 }
 
-impl Path {
-    fn from_str(str: &str) -> Self {
+impl FromStr for Path {
+    type Err = Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
         let mut chars = str.chars().peekable();
 
         let mut first = Vec::new();
@@ -79,7 +89,7 @@ impl Path {
             if c == 'L' || c == 'R' {
                 if !distance.is_empty() {
                     let d: String = std::mem::take(&mut distance).into_iter().collect();
-                    let step = Step(rot, d.parse::<u8>().unwrap());
+                    let step = Step(rot, d.parse::<u8>()?);
                     rest.push(step);
                 }
 
@@ -93,35 +103,31 @@ impl Path {
             }
         }
 
-        let d: String = distance.into_iter().collect();
-        let step = Step(rot, d.parse::<u8>().unwrap());
-        rest.push(step);
+        if !rest.is_empty() || !distance.is_empty() {
+            let d: String = distance.into_iter().collect();
+            let step = Step(rot, d.parse::<u8>()?);
+            rest.push(step);
+        }
 
-        Path {
-            forward: first.into_iter().collect::<String>().parse::<u8>().unwrap(),
+        Ok(Path {
+            forward: first.into_iter().collect::<String>().parse::<u8>()?,
             rest,
-        }
+        })
     }
+}
 
-    fn traverse(&self, map: &Map) -> Monkey {
-        let start = map
-            .0
-            .keys()
-            .filter(|p| p.y() == 1)
-            .min_by_key(|p| p.x())
-            .unwrap()
-            .to_owned();
-
-        let mut monkey = Monkey::new(start);
+impl Path {
+    fn traverse(&self, map: &Map) -> Result<Monkey> {
+        let mut monkey = Monkey::new(map.top_left());
 
-        monkey.forward(map, self.forward);
+        monkey.forward(map, self.forward)?;
 
         for Step(rot, distance) in &self.rest {
             monkey.rotate(rot);
-            monkey.forward(map, *distance);
+            monkey.forward(map, *distance)?;
         }
 
-        monkey
+        Ok(monkey)
     }
 }
 
@@ -133,21 +139,30 @@ impl Monkey {
         Monkey(start, Orient::Right)
     }
 
-    fn forward(&mut self, map: &Map, distance: u8) {
+    fn password(&self) -> i32 {
+        let orient_score = match self.1 {
+            Orient::Right => 0,
+            Orient::Down => 1,
+            Orient::Left => 2,
+            Orient::Up => 3,
+        };
+
+        1000 * self.0.y() + 4 * self.0.x() + orient_score
+    }
+
+    fn forward(&mut self, map: &Map, distance: u8) -> Result<()> {
         for _ in 0..distance {
-            let (p, o) = map.next(&self.0, &self.1);
-            if let Some(s) = map.0.get(&p) {
-                match s {
-                    Square::Empty => {
-                        self.0 = p;
-                        self.1 = o;
-                    }
-                    Square::Rock => break,
+            let (p, o) = map.next(&self.0, &self.1)?;
+            match map.0.get(&p) {
+                Some(Square::Empty) => {
+                    self.0 = p;
+                    self.1 = o;
                 }
-            } else {
-                unreachable!()
+                Some(Square::Rock) => break,
+                None => return Err(not_a_cube()),
             }
         }
+        Ok(())
     }
 
     fn rotate(&mut self, rot: &Rot) {
@@ -184,404 +199,359 @@ impl Orient {
     }
 }
 
+#[derive(Clone)]
 enum Square {
     Empty,
     Rock,
 }
 
-#[derive(Debug)]
-struct Neighbours(Face, Face, Orient, Face, Orient, Face, Orient, Face, Orient);
-
-struct Map(HashMap<Point2d, Square>, Vec<Neighbours>, bool);
+#[derive(Clone)]
+pub struct Map(Grid<Square>, Option<Cube>);
 
 impl Map {
-    fn from_strs(strs: &[String]) -> Self {
-        let mut y = 1;
-        let mut map = HashMap::new();
-        for line in strs {
-            let mut x = 1;
-            for char in line.chars() {
-                let point = Point2d::new(x, y);
-                if char == '.' {
-                    map.insert(point, Square::Empty);
-                } else if char == '#' {
-                    map.insert(point, Square::Rock);
-                }
-
-                x += 1;
-            }
-            y += 1;
+    pub fn from_strs(strs: &[String]) -> Result<Self> {
+        let map = Grid::parse_at(Point2d::new(1, 1), strs, |c| match c {
+            '.' => Ok(Some(Square::Empty)),
+            '#' => Ok(Some(Square::Rock)),
+            ' ' => Ok(None),
+            _ => Err(Error::invalid_value(format!("unknown tile {:?}", c))),
+        })?;
+
+        if map.row(1).next().is_none() {
+            return Err(Error::missing_section("map"));
         }
 
-        let mut width = map.len() as i32;
-        for y in 1..y {
-            width = map
-                .keys()
-                .filter(|p| p.y() == y)
-                .map(|p| p.x())
-                .max()
-                .unwrap_or_default()
-                - map
-                    .keys()
-                    .filter(|p| p.y() == y)
-                    .map(|p| p.x())
-                    .min()
-                    .unwrap_or_default();
-        }
+        Ok(Map(map, None))
+    }
 
-        let top_left = map
-            .keys()
-            .filter(|p| p.y() == 1)
-            .min_by_key(|p| p.x())
-            .unwrap()
-            .to_owned();
+    fn top_left(&self) -> Point2d {
+        self.0.row(1).next().expect("checked when parsed").0
+    }
 
-        let side = Face(
-            top_left.clone(),
-            top_left.add_x(width),
-            top_left.add_y(width),
-            top_left.add_x(width).add_y(width),
-        );
+    fn make_cube(&mut self) -> Result<()> {
+        self.1 = Some(Cube::fold(self)?);
+        Ok(())
+    }
 
-        let mut sides = vec![side.clone()];
-        let mut remaining_sides = vec![side];
-
-        while let Some(side) = remaining_sides.pop() {
-            let top_left = &side.0;
-            let bottom_right = &side.3;
-            if map.contains_key(&top_left.left()) {
-                let side = Face(
-                    top_left.left().add_x(-width),
-                    top_left.left(),
-                    top_left.left().add_x(-width).add_y(width),
-                    top_left.left().add_y(width),
-                );
-
-                if !sides.contains(&side) {
-                    sides.push(side.clone());
-                    remaining_sides.push(side);
-                }
-            }
-            if map.contains_key(&top_left.up()) {
-                let side = Face(
-                    top_left.up().add_y(-width),
-                    top_left.up().add_y(-width).add_x(width),
-                    top_left.up(),
-                    top_left.up().add_x(width),
-                );
-
-                if !sides.contains(&side) {
-                    sides.push(side.clone());
-                    remaining_sides.push(side);
-                }
-            }
-            if map.contains_key(&bottom_right.right()) {
-                let side = Face(
-                    bottom_right.right().add_y(-width),
-                    bottom_right.right().add_y(-width).add_x(width),
-                    bottom_right.right(),
-                    bottom_right.right().add_x(width),
-                );
-
-                if !sides.contains(&side) {
-                    sides.push(side.clone());
-                    remaining_sides.push(side);
-                }
-            }
-            if map.contains_key(&bottom_right.down()) {
-                let side = Face(
-                    bottom_right.down().add_x(-width),
-                    bottom_right.down(),
-                    bottom_right.down().add_x(-width).add_y(width),
-                    bottom_right.down().add_y(width),
-                );
-
-                if !sides.contains(&side) {
-                    sides.push(side.clone());
-                    remaining_sides.push(side);
-                }
-            }
+    fn next(&self, point: &Point2d, orient: &Orient) -> Result<(Point2d, Orient)> {
+        let step = |p: &Point2d, orient: &Orient| match orient {
+            Orient::Down => p.down(),
+            Orient::Up => p.up(),
+            Orient::Left => p.left(),
+            Orient::Right => p.right(),
+        };
+
+        let next = step(point, orient);
+        if self.0.contains(&next) {
+            return Ok((next, orient.clone()));
         }
 
-        let mut side_faces = Vec::new();
-
-        for side in &sides {
-            let l = left_face(side, &sides);
-            let r = right_face(side, &sides);
-            let u = up_face(side, &sides);
-            let d = down_face(side, &sides);
-
-            if let Some(left) = l {
-                if r.is_some() {
-                    unimplemented!()
-                } else if let Some(up) = u {
-                    if d.is_some() {
-                        unimplemented!()
-                    } else if let Some(down) = down_face(left, &sides) {
-                        if let Some(uu) = up_face(up, &sides) {
-                            if let Some(right) = right_face(uu, &sides) {
-                                let sides = Neighbours(
-                                    side.clone(),
-                                    left.clone(),
-                                    Orient::Right,
-                                    up.clone(),
-                                    Orient::Down,
-                                    right.clone(),
-                                    Orient::Right,
-                                    down.clone(),
-                                    Orient::Right,
-                                );
-                                side_faces.push(sides);
-                            } else {
-                                unimplemented!();
-                            }
-                        } else {
-                            unimplemented!();
-                        }
-                    } else {
-                        unimplemented!()
-                    }
-                } else if d.is_some() {
-                    unimplemented!()
-                } else if let Some(down) = down_face(left, &sides) {
-                    if let Some(right) = down_face(down, &sides) {
-                        if let Some(rl) = left_face(right, &sides) {
-                            if let Some(up) = down_face(rl, &sides) {
-                                let sides = Neighbours(
-                                    side.clone(),
-                                    left.clone(),
-                                    Orient::Right,
-                                    up.clone(),
-                                    Orient::Down,
-                                    right.clone(),
-                                    Orient::Right,
-                                    down.clone(),
-                                    Orient::Right,
-                                );
-                                side_faces.push(sides);
-                            } else {
-                                unimplemented!()
-                            }
-                        } else {
-                            unimplemented!()
-                        }
-                    } else {
-                        unimplemented!()
-                    }
-                } else {
-                    unimplemented!()
-                }
-            } else if let Some(right) = r {
-                if u.is_some() {
-                    unimplemented!()
-                } else if let Some(down) = d {
-                    if let Some(down_down) = down_face(down, &sides) {
-                        if let Some(left) = left_face(down_down, &sides) {
-                            if let Some(up) = down_face(left, &sides) {
-                                let sides = Neighbours(
-                                    side.clone(),
-                                    left.clone(),
-                                    Orient::Left,
-                                    up.clone(),
-                                    Orient::Left,
-                                    right.clone(),
-                                    Orient::Left,
-                                    down.clone(),
-                                    Orient::Up,
-                                );
-                                side_faces.push(sides);
-                            } else {
-                                unimplemented!()
-                            }
-                        } else {
-                            unimplemented!()
-                        }
-                    } else if let Some(up) = up_face(right, &sides) {
-                        if let Some(left) = up_face(up, &sides) {
-                            let sides = Neighbours(
-                                side.clone(),
-                                left.clone(),
-                                Orient::Left,
-                                up.clone(),
-                                Orient::Left,
-                                right.clone(),
-                                Orient::Left,
-                                down.clone(),
-                                Orient::Up,
-                            );
-                            side_faces.push(sides);
-                        } else {
-                            unimplemented!();
-                        }
-                    } else {
-                        unimplemented!()
-                    }
-                } else {
-                    unimplemented!()
-                }
-            } else if let Some(up) = u {
-                if let Some(down) = d {
-                    if let Some(right) = right_face(up, &sides) {
-                        if let Some(left) = left_face(down, &sides) {
-                            let sides = Neighbours(
-                                side.clone(),
-                                left.clone(),
-                                Orient::Up,
-                                up.clone(),
-                                Orient::Down,
-                                right.clone(),
-                                Orient::Down,
-                                down.clone(),
-                                Orient::Up,
-                            );
-                            side_faces.push(sides);
-                        } else {
-                            unimplemented!()
-                        }
-                    } else {
-                        unimplemented!()
-                    }
-                } else if let Some(right) = right_face(up, &sides) {
-                    if let Some(ru) = up_face(right, &sides) {
-                        if let Some(left) = up_face(ru, &sides) {
-                            if let Some(down) = right_face(left, &sides) {
-                                let sides = Neighbours(
-                                    side.clone(),
-                                    left.clone(),
-                                    Orient::Up,
-                                    up.clone(),
-                                    Orient::Down,
-                                    right.clone(),
-                                    Orient::Down,
-                                    down.clone(),
-                                    Orient::Up,
-                                );
-                                side_faces.push(sides);
-                            } else {
-                                unimplemented!()
-                            }
-                        } else {
-                            unimplemented!()
-                        }
-                    } else {
-                        unimplemented!()
-                    }
-                } else {
-                    unimplemented!();
+        match &self.1 {
+            Some(cube) => cube.wrap(point, orient).ok_or_else(not_a_cube),
+            None => {
+                let back = orient.reverse();
+                let mut wrapped = point.clone();
+                while self.0.contains(&step(&wrapped, &back)) {
+                    wrapped = step(&wrapped, &back);
                 }
-            } else {
-                unimplemented!();
+                Ok((wrapped, orient.clone()))
             }
         }
+    }
+}
+
+fn not_a_cube() -> Error {
+    Error::unsolvable("the map is not a cube net")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Vec3(i32, i32, i32);
+
+impl Vec3 {
+    fn dot(&self, other: &Vec3) -> i32 {
+        self.0 * other.0 + self.1 * other.1 + self.2 * other.2
+    }
+}
 
-        Map(map, side_faces, false)
+impl Add for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, other: Vec3) -> Vec3 {
+        Vec3(self.0 + other.0, self.1 + other.1, self.2 + other.2)
     }
+}
 
-    fn make_cube(&mut self) {
-        self.2 = true
+impl Sub for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, other: Vec3) -> Vec3 {
+        self + -other
     }
+}
 
-    fn next(&self, point: &Point2d, orient: &Orient) -> (Point2d, Orient) {
-        let next = |p: &Point2d| match orient {
-            Orient::Down => p.down(),
-            Orient::Up => p.up(),
-            Orient::Left => p.left(),
-            Orient::Right => p.right(),
-        };
+impl Neg for Vec3 {
+    type Output = Vec3;
+
+    fn neg(self) -> Vec3 {
+        Vec3(-self.0, -self.1, -self.2)
+    }
+}
 
-        let prev = |p: &Point2d| match orient {
-            Orient::Down => p.up(),
-            Orient::Up => p.down(),
-            Orient::Left => p.right(),
-            Orient::Right => p.left(),
+impl Mul<i32> for Vec3 {
+    type Output = Vec3;
+
+    fn mul(self, n: i32) -> Vec3 {
+        Vec3(self.0 * n, self.1 * n, self.2 * n)
+    }
+}
+
+/// Where one face of the net ends up once folded: its outward normal, and
+/// the directions that right and down on the map point in.
+#[derive(Debug, Clone)]
+struct Face {
+    normal: Vec3,
+    right: Vec3,
+    down: Vec3,
+}
+
+impl Face {
+    /// The neighbouring face in the net in direction `orient`, folded over
+    /// the shared edge.
+    fn fold(&self, orient: &Orient) -> Face {
+        let (normal, right, down) = match orient {
+            Orient::Right => (self.right, -self.normal, self.down),
+            Orient::Left => (-self.right, self.normal, self.down),
+            Orient::Down => (self.down, self.right, -self.normal),
+            Orient::Up => (-self.down, self.right, self.normal),
         };
+        Face {
+            normal,
+            right,
+            down,
+        }
+    }
 
-        let mut ret = next(point);
-        let mut or = orient.clone();
-        if !self.0.contains_key(&ret) {
-            if !self.2 {
-                while self.0.contains_key(&prev(&ret)) {
-                    ret = prev(&ret);
-                }
-            } else {
-                let faces = &self.1;
-                let p = point;
-
-                let face = match orient {
-                    Orient::Left => faces.iter().find(|f| {
-                        f.0 .0.x() == p.x() && f.0 .0.y() <= p.y() && f.0 .2.y() >= p.y()
-                    }),
-                    Orient::Right => faces.iter().find(|f| {
-                        f.0 .1.x() == p.x() && f.0 .0.y() <= p.y() && f.0 .2.y() >= p.y()
-                    }),
-                    Orient::Up => faces.iter().find(|f| {
-                        f.0 .0.y() == p.y() && f.0 .0.x() <= p.x() && f.0 .1.x() >= p.x()
-                    }),
-                    Orient::Down => faces.iter().find(|f| {
-                        f.0 .2.y() == p.y() && f.0 .0.x() <= p.x() && f.0 .1.x() >= p.x()
-                    }),
-                };
-
-                let current = &face.unwrap().0;
-
-                let (side, next_orient, pos) = match orient {
-                    Orient::Left => (&face.unwrap().1, &face.unwrap().2, p.y() - current.0.y()),
-                    Orient::Up => (&face.unwrap().3, &face.unwrap().4, p.x() - current.0.x()),
-                    Orient::Right => (&face.unwrap().5, &face.unwrap().6, p.y() - current.0.y()),
-                    Orient::Down => (&face.unwrap().7, &face.unwrap().8, p.x() - current.0.x()),
-                };
-
-                or = next_orient.reverse();
-
-                match (orient, next_orient) {
-                    (Orient::Left, Orient::Up) => {
-                        ret = side.0.add_x(pos);
-                    }
-                    (Orient::Left, Orient::Left) => {
-                        ret = side.2.add_y(-pos);
-                    }
-                    (Orient::Right, Orient::Down) => {
-                        ret = side.2.add_x(pos);
-                    }
-                    (Orient::Right, Orient::Right) => {
-                        ret = side.3.add_y(-pos);
-                    }
-                    (Orient::Up, Orient::Left) => {
-                        ret = side.0.add_y(pos);
-                    }
-                    (Orient::Up, Orient::Down) => {
-                        ret = side.2.add_x(pos);
-                    }
-                    (Orient::Down, Orient::Up) => {
-                        ret = side.0.add_x(pos);
-                    }
-                    (Orient::Down, Orient::Right) => {
-                        ret = side.1.add_y(pos);
-                    }
-                    _ => unimplemented!(),
+    fn direction(&self, orient: &Orient) -> Vec3 {
+        match orient {
+            Orient::Right => self.right,
+            Orient::Left => -self.right,
+            Orient::Down => self.down,
+            Orient::Up => -self.down,
+        }
+    }
+
+    fn orient(&self, direction: Vec3) -> Option<Orient> {
+        [Orient::Right, Orient::Left, Orient::Down, Orient::Up]
+            .into_iter()
+            .find(|o| self.direction(o) == direction)
+    }
+}
+
+/// The map folded into a cube, with faces keyed by their position in the
+/// net counted in whole faces.
+#[derive(Debug, Clone)]
+struct Cube {
+    side: i32,
+    faces: HashMap<(i32, i32), Face>,
+}
+
+impl Cube {
+    fn fold(map: &Map) -> Result<Cube> {
+        let tiles = map.0.len() as i32;
+        let side = (1..).find(|s| 6 * s * s >= tiles).unwrap_or_default();
+        if 6 * side * side != tiles {
+            return Err(not_a_cube());
+        }
+
+        let face_of = |p: &Point2d| ((p.x() - 1).div_euclid(side), (p.y() - 1).div_euclid(side));
+        let corner = |(x, y): (i32, i32)| Point2d::new(x * side + 1, y * side + 1);
+
+        let start = face_of(&map.top_left());
+        let mut faces = HashMap::from([(
+            start,
+            Face {
+                normal: Vec3(0, 0, 1),
+                right: Vec3(1, 0, 0),
+                down: Vec3(0, 1, 0),
+            },
+        )]);
+        let mut unfolded = vec![start];
+        while let Some((x, y)) = unfolded.pop() {
+            let face = faces[&(x, y)].clone();
+            for (orient, next) in [
+                (Orient::Right, (x + 1, y)),
+                (Orient::Left, (x - 1, y)),
+                (Orient::Down, (x, y + 1)),
+                (Orient::Up, (x, y - 1)),
+            ] {
+                if map.0.contains(&corner(next)) && !faces.contains_key(&next) {
+                    faces.insert(next, face.fold(&orient));
+                    unfolded.push(next);
                 }
             }
         }
 
-        (ret, or)
+        // With 6 * side * side tiles, six connected faces only cover them all
+        // if every face is full, and they only make a cube if no two faces
+        // fold onto the same side.
+        let normals: Vec<_> = faces.values().map(|f| f.normal).collect();
+        let overlaps = normals
+            .iter()
+            .enumerate()
+            .any(|(i, n)| normals[i + 1..].contains(n));
+        if faces.len() != 6 || overlaps || map.0.points().any(|p| !faces.contains_key(&face_of(&p)))
+        {
+            return Err(not_a_cube());
+        }
+
+        Ok(Cube { side, faces })
+    }
+
+    /// Where walking off the edge of the net from `point` ends up, and the
+    /// way it is then facing.
+    fn wrap(&self, point: &Point2d, orient: &Orient) -> Option<(Point2d, Orient)> {
+        let side = self.side;
+        let (x, y) = (point.x() - 1, point.y() - 1);
+        let face = self.faces.get(&(x.div_euclid(side), y.div_euclid(side)))?;
+        let (u, v) = (x.rem_euclid(side), y.rem_euclid(side));
+
+        // Measured in half tiles from the centre of the cube, so that the
+        // middle of every tile is a whole number.
+        let position =
+            face.normal * side + face.right * (2 * u + 1 - side) + face.down * (2 * v + 1 - side);
+
+        let direction = face.direction(orient);
+        let position = position + direction - face.normal;
+
+        let ((fx, fy), next) = self.faces.iter().find(|(_, f)| f.normal == direction)?;
+        let u = (position.dot(&next.right) + side - 1) / 2;
+        let v = (position.dot(&next.down) + side - 1) / 2;
+
+        Some((
+            Point2d::new(fx * side + u + 1, fy * side + v + 1),
+            next.orient(-face.normal)?,
+        ))
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-struct Face(Point2d, Point2d, Point2d, Point2d);
+#[cfg(test)]
+mod test {
+    use super::{Day22, Map, Orient, Path};
+    use crate::solution::example;
 
-fn left_face<'a>(face: &Face, faces: &'a [Face]) -> Option<&'a Face> {
-    faces.iter().find(|f| f.1.is_neighbour(&face.0))
-}
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day22, include_str!("examples/day22.txt")),
+            ("6032".to_string(), "5031".to_string())
+        );
+    }
 
-fn right_face<'a>(face: &Face, faces: &'a [Face]) -> Option<&'a Face> {
-    faces.iter().find(|f| f.0.is_neighbour(&face.1))
-}
+    /// An open map with `side` by `side` tiles for every `#` in `faces`.
+    fn net<S: AsRef<str>>(faces: &[S], side: usize) -> Map {
+        let lines: Vec<_> = faces
+            .iter()
+            .flat_map(|row| {
+                let line: String = row
+                    .as_ref()
+                    .chars()
+                    .map(|c| if c == '#' { "." } else { " " }.repeat(side))
+                    .collect();
+                std::iter::repeat_n(line.trim_end().to_string(), side)
+            })
+            .collect();
+
+        Map::from_strs(&lines).unwrap()
+    }
 
-fn down_face<'a>(face: &Face, faces: &'a [Face]) -> Option<&'a Face> {
-    faces.iter().find(|f| f.1.is_neighbour(&face.3))
-}
+    fn password(map: &Map, path: &str) -> i32 {
+        path.parse::<Path>()
+            .unwrap()
+            .traverse(map)
+            .unwrap()
+            .password()
+    }
+
+    #[test]
+    fn cube_wrapping() {
+        // Laid out like the puzzle input, with four tiles a side.
+        let mut map = net(&[" ##", " # ", "## ", "#  "], 4);
+        map.make_cube().unwrap();
+
+        // Going all the way round the cube in any direction comes back to the
+        // start, facing the same way.
+        assert_eq!(password(&map, "16"), 1020);
+        assert_eq!(password(&map, "0R16"), 1021);
+        assert_eq!(password(&map, "0R0R16"), 1022);
+        assert_eq!(password(&map, "0L16"), 1023);
+
+        // Off the top of the first face and onto the left edge of the last.
+        assert_eq!(password(&map, "0L1"), 13004);
+    }
+
+    /// The eleven cube nets, up to rotation and reflection.
+    const NETS: [&[&str]; 11] = [
+        &["#   ", "####", "#   "],
+        &["#   ", "####", " #  "],
+        &["#   ", "####", "  # "],
+        &["#   ", "####", "   #"],
+        &[" #  ", "####", " #  "],
+        &[" #  ", "####", "  # "],
+        &["##  ", " ###", " #  "],
+        &["##  ", " ###", "  # "],
+        &["##  ", " ###", "   #"],
+        &["##  ", " ## ", "  ##"],
+        &["###  ", "  ###"],
+    ];
+
+    fn transpose(faces: &[&str]) -> Vec<String> {
+        let width = faces.iter().map(|r| r.len()).max().unwrap_or_default();
+        (0..width)
+            .map(|x| {
+                faces
+                    .iter()
+                    .map(|r| r.chars().nth(x).unwrap_or(' '))
+                    .collect()
+            })
+            .collect()
+    }
 
-fn up_face<'a>(face: &Face, faces: &'a [Face]) -> Option<&'a Face> {
-    faces.iter().find(|f| f.3.is_neighbour(&face.1))
+    fn check_folds(map: &mut Map, side: i32) {
+        map.make_cube().unwrap();
+
+        for point in map.0.points() {
+            for orient in [Orient::Right, Orient::Down, Orient::Left, Orient::Up] {
+                // Turning round after a step leads straight back.
+                let (next, facing) = map.next(&point, &orient).unwrap();
+                assert!(map.0.contains(&next));
+                let (back, _) = map.next(&next, &facing.reverse()).unwrap();
+                assert_eq!(back, point);
+
+                // Going all the way round comes back to the start.
+                let mut walked = (point.clone(), orient.clone());
+                for _ in 0..4 * side {
+                    walked = map.next(&walked.0, &walked.1).unwrap();
+                }
+                assert_eq!(walked, (point.clone(), orient));
+            }
+        }
+    }
+
+    #[test]
+    fn every_net_folds() {
+        for faces in NETS {
+            for side in [1, 3] {
+                check_folds(&mut net(faces, side as usize), side);
+                check_folds(&mut net(&transpose(faces), side as usize), side);
+            }
+        }
+    }
+
+    #[test]
+    fn not_a_cube() {
+        assert!(net(&["######"], 2).make_cube().is_err());
+        assert!(net(&["###", "###"], 2).make_cube().is_err());
+        assert!(net(&["####", "#", "#"], 2).make_cube().is_err());
+        assert!(net(&["##", "##"], 3).make_cube().is_err());
+    }
 }
@@ -1,80 +1,109 @@
-use std::collections::{HashMap, HashSet, VecDeque};
-
-use super::point::Point2d;
-
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let mut map = Map::from_strs(&lines);
-    let mut rules = Rules::new();
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+};
+
+use super::{grid::Grid, point::Point2d};
+use crate::{
+    error::{Error, Result},
+    solution::Solution,
+    visualize::Frame,
+};
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+    type Parsed = Map;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        Map::from_strs(&lines)
+    }
 
-    for i in 1..10000 {
-        let moved = map.turn(&mut rules);
+    fn part1(&self, map: &Self::Parsed) -> Result<i32> {
+        let mut map = map.clone();
+        let mut rules = Rules::new();
 
-        if i == 10 {
-            println!("Part 1 {}", map.empty_spaces_count());
+        for _ in 0..10 {
+            map.turn(&mut rules);
         }
 
-        if !moved {
-            println!("Part 2 {}", i);
-            break;
-        }
+        Ok(map.empty_spaces_count())
     }
 
-    Ok(())
-}
+    fn part2(&self, map: &Self::Parsed) -> Result<i32> {
+        let mut map = map.clone();
+        let mut rules = Rules::new();
 
-#[derive(Debug)]
-struct Map(HashSet<Point2d>);
+        for i in 1..10000 {
+            if !map.turn(&mut rules) {
+                return Ok(i);
+            }
+        }
 
-impl Map {
-    fn from_strs(strs: &[String]) -> Self {
-        let mut y = 1;
-        let mut map = HashSet::new();
-        for line in strs {
-            let mut x = 1;
-            for char in line.chars() {
-                if char == '#' {
-                    let point = Point2d::new(x, y);
-                    map.insert(point);
-                }
+        Err(Error::unsolvable("elves never stopped moving"))
+    }
 
-                x += 1;
+    /// Spreads the elves out one round per frame until none of them move.
+    fn visualize(&self, map: &Self::Parsed, show: &mut dyn FnMut(Frame)) -> Result<bool> {
+        let mut map = map.clone();
+        let mut rules = Rules::new();
+
+        show(map.frame());
+        for _ in 1..10000 {
+            let moved = map.turn(&mut rules);
+            show(map.frame());
+            if !moved {
+                return Ok(true);
             }
-            y += 1;
         }
-        Map(map)
+
+        Err(Error::unsolvable("elves never stopped moving"))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Elf;
+
+impl Display for Elf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#")
     }
+}
 
-    #[allow(dead_code)]
-    fn print(&self) {
-        let map = &self.0;
-        let min_x = map.iter().min_by_key(|p| p.x()).unwrap().x();
-        let min_y = map.iter().min_by_key(|p| p.y()).unwrap().y();
+#[derive(Debug, Clone)]
+pub struct Map(Grid<Elf>);
 
-        let max_x = map.iter().max_by_key(|p| p.x()).unwrap().x();
-        let max_y = map.iter().max_by_key(|p| p.y()).unwrap().y();
+impl Map {
+    pub fn from_strs(strs: &[String]) -> Result<Self> {
+        let map = Grid::parse_at(Point2d::new(1, 1), strs, |c| match c {
+            '#' => Ok(Some(Elf)),
+            '.' => Ok(None),
+            _ => Err(Error::invalid_value(format!("unknown tile {:?}", c))),
+        })?;
+        Ok(Map(map))
+    }
 
-        for y in min_y..max_y + 1 {
-            for x in min_x..max_x + 1 {
-                if map.contains(&Point2d::new(x, y)) {
-                    print!("#");
-                } else {
-                    print!(" ");
-                }
-            }
-            println!()
+    fn frame(&self) -> Frame {
+        let mut grid = Grid::new();
+        for elf in self.0.points() {
+            grid.insert(elf, '#');
         }
+        Frame::new(grid)
     }
 
     fn turn(&mut self, rules: &mut Rules) -> bool {
         let mut moves = Vec::new();
-        for elf in &self.0 {
-            if self.is_alone(elf) {
+        for elf in self.0.points() {
+            if self.is_alone(&elf) {
                 continue;
             }
 
             for rule in &rules.0 {
-                if rule.should_move(self, elf) {
-                    moves.push((elf.clone(), rule.new_position(elf)));
+                if rule.should_move(self, &elf) {
+                    moves.push((elf.clone(), rule.new_position(&elf)));
                     break;
                 }
             }
@@ -100,20 +129,7 @@ impl Map {
     }
 
     fn is_alone(&self, elf: &Point2d) -> bool {
-        let n = elf.up();
-        let s = elf.down();
-        [
-            n.left(),
-            n.right(),
-            n,
-            elf.right(),
-            s.right(),
-            s.left(),
-            s,
-            elf.left(),
-        ]
-        .iter()
-        .all(|p| !self.0.contains(p))
+        self.0.neighbours8(elf).next().is_none()
     }
 
     // fn elves(&self) -> Vec<&Point2d> {
@@ -121,27 +137,15 @@ impl Map {
     // }
 
     fn move_elf(&mut self, from: &Point2d, to: &Point2d) {
-        self.0.remove(from);
-        self.0.insert(to.clone());
+        if let Some(elf) = self.0.remove(from) {
+            self.0.insert(to.clone(), elf);
+        }
     }
 
     fn empty_spaces_count(&self) -> i32 {
-        let elves = &self.0;
-
-        let min_x = elves.iter().min_by_key(|p| p.x()).unwrap().x();
-        let min_y = elves.iter().min_by_key(|p| p.y()).unwrap().y();
-        let max_x = elves.iter().max_by_key(|p| p.x()).unwrap().x();
-        let max_y = elves.iter().max_by_key(|p| p.y()).unwrap().y();
-
-        let mut count = 0;
-        for x in min_x..max_x + 1 {
-            for y in min_y..max_y + 1 {
-                if !self.0.contains(&Point2d::new(x, y)) {
-                    count += 1;
-                }
-            }
-        }
-        count
+        self.0
+            .bounds()
+            .map_or(0, |b| b.area() - self.0.len() as i32)
     }
 }
 
@@ -193,3 +197,17 @@ impl Rules {
         self.0.push_back(first);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Day23;
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day23, include_str!("examples/day23.txt")),
+            ("110".to_string(), "20".to_string())
+        );
+    }
+}
@@ -1,45 +1,109 @@
-use std::collections::{HashMap, HashSet};
-
 use itertools::Itertools;
 
-use super::point::Point2d;
+use super::{
+    grid::{Bounds, Grid},
+    point::Point2d,
+    search::{Found, Graph, Search},
+};
+use crate::{
+    error::{Error, Result},
+    solution::Solution,
+    visualize::Frame,
+};
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+    type Parsed = Valley;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        Valley::from_strs(&lines)
+    }
+
+    fn part1(&self, valley: &Self::Parsed) -> Result<i32> {
+        let (start, end) = valley.entrances();
+
+        Expedition::new(valley).crossing(&start, &end, 0)
+    }
+
+    fn part2(&self, valley: &Self::Parsed) -> Result<i32> {
+        let expedition = Expedition::new(valley);
+        let (start, end) = valley.entrances();
+
+        let there = expedition.crossing(&start, &end, 0)?;
+        let back = expedition.crossing(&end, &start, there)?;
+        let there_again = expedition.crossing(&start, &end, there + back)?;
+
+        Ok(there + back + there_again)
+    }
+
+    /// Follows the expedition there, back and there again, one minute per
+    /// frame.
+    fn visualize(&self, valley: &Self::Parsed, show: &mut dyn FnMut(Frame)) -> Result<bool> {
+        let expedition = Expedition::new(valley);
+        let (start, end) = valley.entrances();
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let mut valley = Valley::from_strs(&lines);
+        let mut route = vec![start.clone()];
+        for (from, to) in [(&start, &end), (&end, &start), (&start, &end)] {
+            let minute = route.len() as i32 - 1;
+            route.extend(expedition.route(from, to, minute)?.into_iter().skip(1));
+        }
 
-    parts(&mut valley);
+        let mut valley = valley.clone();
+        for (minute, position) in route.iter().enumerate() {
+            if minute > 0 {
+                valley.step();
+            }
+            show(valley.frame(position));
+        }
 
-    Ok(())
+        Ok(true)
+    }
 }
 
-#[derive(Debug)]
-struct Valley {
-    blizzards: HashMap<Point2d, Blizzard>,
+#[derive(Debug, Clone)]
+pub struct Valley {
+    blizzards: Grid<Blizzard>,
     max_x: i32,
     max_y: i32,
 }
 
 impl Valley {
-    fn from_strs(strs: &[String]) -> Self {
+    pub fn from_strs(strs: &[String]) -> Result<Self> {
         let mut lines = strs.iter();
-        let mut blizzards = HashMap::new();
-        lines.next();
+        let mut blizzards = Grid::new();
+        let top = lines
+            .next()
+            .ok_or_else(|| Error::missing_section("valley"))?;
+        if !top.starts_with("#.") {
+            return Err(Error::parse("expected the entrance at the top left").at_line(0, top));
+        }
 
-        let mut y = 1;
-        for line in lines {
-            let mut x = 1;
-            if &line[1..2] == "#" {
+        for (y, line) in (1..).zip(lines) {
+            let at_line = |e: Error| e.at_line(y as usize, line);
+            if line.starts_with("##") {
+                if !line.ends_with(".#") {
+                    return Err(at_line(Error::parse(
+                        "expected the exit at the bottom right",
+                    )));
+                }
                 let max_x = line.len() as i32 - 2;
                 let max_y = y - 1;
-                return Valley {
+                if max_x < 1 || max_y < 1 {
+                    return Err(at_line(Error::invalid_value("the valley is empty")));
+                }
+                return Ok(Valley {
                     blizzards,
                     max_x,
                     max_y,
-                };
+                });
             } else {
                 let mut chars = line.chars();
                 chars.next();
-                for char in chars {
+                for (x, char) in (1..).zip(chars) {
                     let point = Point2d::new(x, y);
                     let directions = match char {
                         '>' => vec![Direction::Right],
@@ -47,56 +111,73 @@ impl Valley {
                         'v' => vec![Direction::Down],
                         '^' => vec![Direction::Up],
                         '.' | '#' => Vec::new(),
-                        _ => unimplemented!(),
+                        _ => {
+                            return Err(at_line(Error::invalid_value(format!(
+                                "unknown tile {:?}",
+                                char
+                            ))))
+                        }
                     };
 
                     if !directions.is_empty() {
                         blizzards.insert(point, Blizzard(directions));
                     }
-                    x += 1;
                 }
             }
-            y += 1;
         }
 
-        unreachable!()
+        Err(Error::missing_section("bottom wall"))
     }
 
-    #[allow(dead_code)]
-    fn print(&self, positions: &HashSet<Point2d>) {
-        for y in 1..self.max_y + 1 {
-            for x in 1..self.max_x + 1 {
-                if let Some(Blizzard(dir)) = self.blizzards.get(&Point2d::new(x, y)) {
-                    if dir.len() == 1 {
-                        let c = match dir[0] {
-                            Direction::Up => "^",
-                            Direction::Left => "<",
-                            Direction::Down => "v",
-                            Direction::Right => ">",
-                        };
-                        print!("{}", c);
-                    } else {
-                        print!("{}", dir.len());
-                    }
-                } else if positions.contains(&Point2d::new(x, y)) {
-                    print!("o")
-                } else {
-                    print!(" ")
-                }
-            }
-            println!()
+    fn entrances(&self) -> (Point2d, Point2d) {
+        (Point2d::new(1, 0), Point2d::new(self.max_x, self.max_y + 1))
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(Point2d::new(1, 1), Point2d::new(self.max_x, self.max_y))
+    }
+
+    /// The valley with its walls, blizzards, and the expedition as `E`.
+    fn frame(&self, expedition: &Point2d) -> Frame {
+        let (start, end) = self.entrances();
+        let walls = Bounds::new(
+            Point2d::new(0, 0),
+            Point2d::new(self.max_x + 1, self.max_y + 1),
+        );
+        let inside = self.bounds();
+
+        let mut grid = Grid::dense(&walls);
+        for p in walls.points() {
+            let c = match self.blizzards.get(&p) {
+                _ if p == *expedition => 'E',
+                Some(Blizzard(dir)) if dir.len() == 1 => match dir[0] {
+                    Direction::Up => '^',
+                    Direction::Left => '<',
+                    Direction::Down => 'v',
+                    Direction::Right => '>',
+                },
+                Some(Blizzard(dir)) => char::from_digit(dir.len() as u32, 10).unwrap_or('*'),
+                None if inside.contains(&p) || p == start || p == end => '.',
+                None => '#',
+            };
+            grid.insert(p, c);
         }
+
+        Frame::new(grid).focused_on(expedition.clone())
     }
 
     fn step(&mut self) {
-        let mut next_points: Vec<_> = std::mem::take(&mut self.blizzards)
+        let (max_x, max_y) = (self.max_x, self.max_y);
+        let dense = Grid::dense(&self.bounds());
+        let blizzards = std::mem::replace(&mut self.blizzards, dense);
+        let mut next_points: Vec<_> = blizzards
             .iter()
             .flat_map(|(p, b)| {
-                b.0.iter().map(|d| match d {
+                b.0.iter().map(move |d| match d {
                     Direction::Up => {
                         let next = p.up();
                         let wrapped = if next.y() == 0 {
-                            next.add_y(self.max_y)
+                            next.add_y(max_y)
                         } else {
                             next
                         };
@@ -104,8 +185,8 @@ impl Valley {
                     }
                     Direction::Down => {
                         let next = p.down();
-                        let wrapped = if next.y() == self.max_y + 1 {
-                            next.add_y(-self.max_y)
+                        let wrapped = if next.y() == max_y + 1 {
+                            next.add_y(-max_y)
                         } else {
                             next
                         };
@@ -114,7 +195,7 @@ impl Valley {
                     Direction::Left => {
                         let next = p.left();
                         let wrapped = if next.x() == 0 {
-                            next.add_x(self.max_x)
+                            next.add_x(max_x)
                         } else {
                             next
                         };
@@ -122,8 +203,8 @@ impl Valley {
                     }
                     Direction::Right => {
                         let next = p.right();
-                        let wrapped = if next.x() == self.max_x + 1 {
-                            next.add_x(-self.max_x)
+                        let wrapped = if next.x() == max_x + 1 {
+                            next.add_x(-max_x)
                         } else {
                             next
                         };
@@ -145,7 +226,7 @@ impl Valley {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Direction {
     Up,
     Down,
@@ -153,58 +234,134 @@ enum Direction {
     Right,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Blizzard(Vec<Direction>);
 
-fn parts(valley: &mut Valley) {
-    let mut minute = 0;
-    let start = Point2d::new(1, 0);
-    let end = Point2d::new(valley.max_x, valley.max_y + 1);
-
-    let mut positions = HashSet::from([start.clone()]);
-    let mut goal = end.clone();
-    let mut part2 = false;
-    loop {
-        let mut completed = false;
-        valley.step();
-        for position in std::mem::take(&mut positions) {
-            if position == goal {
-                completed = true;
-                break;
-            }
+/// Where the blizzards are each minute, for one period after which they
+/// repeat.
+struct Expedition<'a> {
+    valley: &'a Valley,
+    forecast: Vec<Grid<()>>,
+}
 
-            let up = position.up();
-            let down = position.down();
-            let left = position.left();
-            let right = position.right();
+impl<'a> Expedition<'a> {
+    fn new(valley: &'a Valley) -> Self {
+        let period = valley.max_x * valley.max_y / gcd(valley.max_x, valley.max_y);
 
-            for p in [up, down, left, right, position] {
-                if (p != start && p != end)
-                    && (p.x() <= 0 || p.y() <= 0 || p.x() > valley.max_x || p.y() > valley.max_y)
-                {
-                    continue;
+        let mut current = valley.clone();
+        let forecast = (0..period)
+            .map(|_| {
+                let mut occupied = Grid::dense(&valley.bounds());
+                for p in current.blizzards.points() {
+                    occupied.insert(p, ());
                 }
+                current.step();
+                occupied
+            })
+            .collect();
 
-                if !valley.blizzards.contains_key(&p) {
-                    positions.insert(p);
-                }
-            }
-        }
+        Expedition { valley, forecast }
+    }
 
-        if completed {
-            if !part2 {
-                println!("Part 1 {}", minute);
-                part2 = true;
-                goal = start.clone();
-                positions = HashSet::from([Point2d::new(valley.max_x, valley.max_y + 1)]);
-            } else if goal == start {
-                goal = end.clone();
-                positions = HashSet::from([start.clone()]);
-            } else {
-                println!("Part 2 {}", minute);
-                break;
-            }
-        }
-        minute += 1;
+    fn crossing(&self, from: &Point2d, to: &Point2d, minute: i32) -> Result<i32> {
+        self.search(Search::new(), from, to, minute)
+            .map(|found| found.cost as i32)
+    }
+
+    /// Where the expedition is each minute of the quickest crossing,
+    /// starting with `from`.
+    fn route(&self, from: &Point2d, to: &Point2d, minute: i32) -> Result<Vec<Point2d>> {
+        let found = self.search(Search::new().with_paths(), from, to, minute)?;
+        let path = found.path.unwrap_or_default();
+        Ok(path.into_iter().map(|(p, _)| p).collect())
+    }
+
+    fn search(
+        &self,
+        search: Search,
+        from: &Point2d,
+        to: &Point2d,
+        minute: i32,
+    ) -> Result<Found<(Point2d, usize)>> {
+        let minute = minute as usize % self.forecast.len();
+
+        // Waiting is a move too, and the forecast repeats, so the search
+        // runs out of new states if every path is blocked.
+        search
+            .astar(
+                self,
+                (from.clone(), minute),
+                |(p, _)| p == to,
+                |(p, _)| p.x().abs_diff(to.x()) + p.y().abs_diff(to.y()),
+            )
+            .ok_or_else(|| Error::unsolvable("every path is blocked by blizzards"))
+    }
+}
+
+impl<'a> Graph for Expedition<'a> {
+    type Node = (Point2d, usize);
+
+    fn neighbours(&self, (position, minute): &(Point2d, usize)) -> Vec<((Point2d, usize), u32)> {
+        let (start, end) = self.valley.entrances();
+        let bounds = self.valley.bounds();
+        let next = (minute + 1) % self.forecast.len();
+
+        [
+            position.up(),
+            position.down(),
+            position.left(),
+            position.right(),
+            position.clone(),
+        ]
+        .into_iter()
+        .filter(|p| *p == start || *p == end || bounds.contains(p))
+        .filter(|p| !self.forecast[next].contains(p))
+        .map(|p| ((p, next), 1))
+        .collect()
+    }
+}
+
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Day24;
+    use crate::{
+        days::point::Point2d,
+        solution::{example, Solution},
+        visualize::Frame,
+    };
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day24, include_str!("examples/day24.txt")),
+            ("18".to_string(), "54".to_string())
+        );
+    }
+
+    #[test]
+    fn visualize() {
+        let lines = include_str!("examples/day24.txt")
+            .lines()
+            .map(String::from)
+            .collect();
+        let valley = Day24.parse(lines).unwrap();
+        let mut frames: Vec<Frame> = Vec::new();
+
+        assert!(Day24.visualize(&valley, &mut |f| frames.push(f)).unwrap());
+        assert_eq!(frames.len(), 1 + 54);
+
+        let focus: Vec<_> = frames.iter().map(|f| f.focus.clone().unwrap()).collect();
+        assert_eq!(focus[0], Point2d::new(1, 0));
+        assert_eq!(focus[18], Point2d::new(6, 5));
+        assert_eq!(focus[54], Point2d::new(6, 5));
+        assert_eq!(frames[0].grid.get(&focus[0]), Some(&'E'));
     }
 }
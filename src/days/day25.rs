@@ -1,92 +1,137 @@
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let sum: i64 = lines.iter().map(|l| snafu_to_i64(l)).sum();
+use crate::{
+    error::{parse_lines, Error, Result},
+    solution::Solution,
+};
 
-    println!("Part 1 {}", i64_to_snafu(sum));
+pub struct Day25;
 
-    Ok(())
+impl Solution for Day25 {
+    const DAY: u8 = 25;
+    type Parsed = Vec<i64>;
+    type Part1 = String;
+    type Part2 = &'static str;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        parse_lines(&lines, snafu_to_i64)
+    }
+
+    fn part1(&self, numbers: &Self::Parsed) -> Result<String> {
+        let sum: i64 = numbers.iter().sum();
+
+        Ok(i64_to_snafu(sum))
+    }
+
+    fn part2(&self, _: &Self::Parsed) -> Result<&'static str> {
+        Ok("no part 2")
+    }
 }
 
-fn snafu_to_i64(snafu: &str) -> i64 {
-    snafu
-        .chars()
-        .rev()
-        .enumerate()
-        .map(|(i, c)| match c {
-            '=' => -2 * pow5(i),
-            '-' => -pow5(i),
+pub fn snafu_to_i64(snafu: &str) -> Result<i64> {
+    let too_large = || Error::invalid_value(format!("{} does not fit in an i64", snafu));
+
+    // Summed as an i128 because the digits of i64::MIN overshoot it on the
+    // way there.
+    let n = snafu.chars().try_fold(0i128, |n, c| {
+        let digit = match c {
+            '=' => -2,
+            '-' => -1,
             '0' => 0,
-            '1' => pow5(i),
-            '2' => 2 * pow5(i),
-            c => panic!("unexpected {}", c),
-        })
-        .sum()
+            '1' => 1,
+            '2' => 2,
+            c => return Err(Error::parse(format!("unexpected SNAFU digit {:?}", c))),
+        };
+
+        n.checked_mul(5)
+            .and_then(|n| n.checked_add(digit))
+            .ok_or_else(too_large)
+    })?;
+
+    i64::try_from(n).map_err(|_| too_large())
 }
 
-fn i64_to_snafu(i: i64) -> String {
-    let mut remainder = i;
+pub fn i64_to_snafu(i: i64) -> String {
+    if i == 0 {
+        return "0".to_string();
+    }
 
+    // Balanced base 5: a remainder of 3 or 4 is written as -2 or -1 with one
+    // carried into the next digit.
+    let mut remainder = i;
     let mut chars = Vec::new();
-
-    let mut i = 1;
-    while pow5(i) - 2 * sum_pow5(i - 1) <= remainder {
-        i += 1;
+    while remainder != 0 {
+        let (digit, carry) = match remainder.rem_euclid(5) {
+            0 => ('0', 0),
+            1 => ('1', 0),
+            2 => ('2', 0),
+            3 => ('=', 1),
+            4 => ('-', 1),
+            _ => unreachable!(),
+        };
+        chars.push(digit);
+        remainder = remainder.div_euclid(5) + carry;
     }
 
-    for i in (1..i).rev() {
-        if remainder >= 0 {
-            let x = (remainder + 2 * sum_pow5(i - 1)) / pow5(i);
-
-            remainder -= x * pow5(i);
-            chars.push(match x {
-                2 => '2',
-                1 => '1',
-                0 => '0',
-                _ => unreachable!(),
-            });
-        } else {
-            let n = remainder + 2 * sum_pow5(i - 1);
-            if n >= 0 {
-                chars.push('0');
-            } else {
-                let x = (n + 1) / pow5(i);
-                remainder -= (x - 1) * pow5(i);
-                chars.push(match x {
-                    0 => '-',
-                    -1 => '=',
-                    _ => unreachable!(),
-                });
-            }
-        }
+    chars.iter().rev().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+
+    use super::{i64_to_snafu, snafu_to_i64, Day25};
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day25, include_str!("examples/day25.txt")),
+            ("2=-1=0".to_string(), "no part 2".to_string())
+        );
     }
 
-    chars.push(match remainder {
-        -2 => '=',
-        -1 => '-',
-        0 => '0',
-        1 => '1',
-        2 => '2',
-        _ => unreachable!(),
-    });
+    #[test]
+    fn snafu() {
+        for (i, snafu) in [
+            (1, "1"),
+            (2, "2"),
+            (3, "1="),
+            (4, "1-"),
+            (5, "10"),
+            (8, "2="),
+            (9, "2-"),
+            (10, "20"),
+            (15, "1=0"),
+            (20, "1-0"),
+            (2022, "1=11-2"),
+            (12345, "1-0---0"),
+            (314159265, "1121-1110-1=0"),
+        ] {
+            assert_eq!(i64_to_snafu(i), snafu);
+            assert_eq!(snafu_to_i64(snafu), Ok(i));
+        }
 
-    chars.iter().collect()
-}
+        for i in 1..=5000 {
+            assert_eq!(snafu_to_i64(&i64_to_snafu(i)), Ok(i));
+        }
 
-fn pow5(i: usize) -> i64 {
-    let mut x = 1;
-    if i == 0 {
-        x
-    } else {
-        for _ in 0..i {
-            x *= 5;
+        for i in [0, -1, -3, i64::MIN, i64::MAX] {
+            assert_eq!(snafu_to_i64(&i64_to_snafu(i)), Ok(i));
         }
-        x
+
+        assert!(snafu_to_i64("1=3").is_err());
+        assert!(snafu_to_i64(&format!("2{}", "0".repeat(27))).is_err());
     }
-}
 
-fn sum_pow5(i: usize) -> i64 {
-    let mut sum = 0;
-    for i in 0..i + 1 {
-        sum += pow5(i);
+    proptest! {
+        #[test]
+        fn snafu_round_trips(n in any::<i64>()) {
+            prop_assert_eq!(snafu_to_i64(&i64_to_snafu(n)), Ok(n));
+        }
+
+        #[test]
+        fn snafu_is_canonical(snafu in "[12=-][=\\-012]{0,25}") {
+            let n = snafu_to_i64(&snafu).unwrap();
+            prop_assert_eq!(i64_to_snafu(n), snafu);
+        }
     }
-    sum
 }
@@ -1,30 +1,57 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, fmt::Display, str::FromStr};
+
+use crate::{
+    error::{parse_lines, Error, Result},
+    solution::Solution,
+};
 
 #[derive(Debug)]
-struct Backpack<'a> {
-    front: &'a str,
-    back: &'a str,
+pub struct Backpack {
+    front: String,
+    back: String,
 }
 
-impl<'a> Backpack<'a> {
-    fn from_str(str: &'a str) -> Result<Self, String> {
+impl FromStr for Backpack {
+    type Err = Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        if let Some(c) = str.chars().find(|c| !c.is_ascii_alphabetic()) {
+            return Err(Error::invalid_value(format!(
+                "item {:?} is not a letter",
+                c
+            )));
+        }
+        if !str.len().is_multiple_of(2) {
+            return Err(Error::invalid_value(
+                "compartments must hold the same number of items",
+            ));
+        }
+
         let len = str.len() / 2;
-        Ok(Backpack::<'a> {
-            front: &str[..len],
-            back: &str[len..],
+        Ok(Backpack {
+            front: str[..len].to_string(),
+            back: str[len..].to_string(),
         })
     }
+}
+
+impl Display for Backpack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.front, self.back)
+    }
+}
 
-    fn common_score(&self) -> i32 {
+impl Backpack {
+    fn common_score(&self) -> Result<i32> {
         let intersection: Vec<char> = self
             .front_set()
             .intersection(&self.back_set())
             .copied()
             .collect();
 
-        let char = *intersection.first().unwrap();
-
-        score(char)
+        intersection.first().map(|c| score(*c)).ok_or_else(|| {
+            Error::unsolvable("no item is in both compartments").with_text(&self.to_string())
+        })
     }
 
     fn front_set(&self) -> HashSet<char> {
@@ -42,7 +69,7 @@ impl<'a> Backpack<'a> {
     }
 }
 
-fn common_badge((b1, b2, b3): (&Backpack, &Backpack, &Backpack)) -> i32 {
+fn common_badge((b1, b2, b3): (&Backpack, &Backpack, &Backpack)) -> Result<i32> {
     let i1: HashSet<char> = b1
         .full_set()
         .intersection(&b2.full_set())
@@ -51,9 +78,9 @@ fn common_badge((b1, b2, b3): (&Backpack, &Backpack, &Backpack)) -> i32 {
 
     let i2: Vec<char> = i1.intersection(&b3.full_set()).copied().collect();
 
-    let char = *i2.first().unwrap();
-
-    score(char)
+    i2.first().map(|c| score(*c)).ok_or_else(|| {
+        Error::unsolvable("no badge is common to the group").with_text(&b1.to_string())
+    })
 }
 
 fn score(char: char) -> i32 {
@@ -68,23 +95,49 @@ fn score(char: char) -> i32 {
         (char_code - UPPERCASE_A + 27) as i32
     }
 }
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let parsed = lines
-        .iter()
-        .map(|s| Backpack::from_str(s))
-        .collect::<Result<Vec<_>, _>>()?;
 
-    let part1: i32 = parsed.iter().map(|i| i.common_score()).sum();
-    println!("Part 1 {}", part1);
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+    type Parsed = Vec<Backpack>;
+    type Part1 = i32;
+    type Part2 = i32;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        parse_lines(&lines, Backpack::from_str)
+    }
 
-    let part2: i32 = parsed
-        .as_slice()
-        .chunks(3)
-        .map(|chunk| (&chunk[0], &chunk[1], &chunk[2]))
-        .map(common_badge)
-        .sum();
+    fn part1(&self, parsed: &Self::Parsed) -> Result<i32> {
+        parsed.iter().map(|i| i.common_score()).sum()
+    }
 
-    println!("Part 2 {}", part2);
+    fn part2(&self, parsed: &Self::Parsed) -> Result<i32> {
+        if !parsed.len().is_multiple_of(3) {
+            return Err(Error::invalid_value(
+                "backpacks must come in groups of three",
+            ));
+        }
+
+        parsed
+            .as_slice()
+            .chunks(3)
+            .map(|chunk| (&chunk[0], &chunk[1], &chunk[2]))
+            .map(common_badge)
+            .sum()
+    }
+}
 
-    Ok(())
+#[cfg(test)]
+mod test {
+    use super::Day3;
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day3, include_str!("examples/day3.txt")),
+            ("157".to_string(), "70".to_string())
+        );
+    }
 }
@@ -1,62 +1,109 @@
+use std::str::FromStr;
+
+use super::interval::IntervalSet;
+use crate::{
+    error::{parse_lines, Error, Result},
+    scanner::Scanner,
+    solution::Solution,
+};
+
 #[derive(Debug)]
-struct Pair {
+pub struct Pair {
     first: (u8, u8),
     second: (u8, u8),
 }
 
+impl FromStr for Pair {
+    type Err = Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let range = |s: &mut Scanner| -> Result<(u8, u8)> {
+            let at = s.clone();
+            let start = s.integer()?;
+            s.literal("-")?;
+            let end = s.integer()?;
+            if start > end {
+                return Err(at.error(Error::invalid_value(format!(
+                    "range {}-{} ends before it starts",
+                    start, end
+                ))));
+            }
+            Ok((start, end))
+        };
+
+        let (first, second) = Scanner::parse(str, |s| {
+            let first = range(s)?;
+            s.literal(",")?;
+            Ok((first, range(s)?))
+        })?;
+
+        Ok(Pair { first, second })
+    }
+}
+
 impl Pair {
-    fn from_str(str: &str) -> Result<Self, String> {
-        let first_split = str.find('-').ok_or("Missing first -")?;
-        let comma = str.find(',').ok_or("Missing ,")?;
-        let second_split = comma + str[comma..].find('-').ok_or("Missing second -")?;
-
-        let f1 = str[..first_split]
-            .parse::<u8>()
-            .map_err(|e| format!("{}", e))?;
-        let f2 = str[first_split + 1..comma]
-            .parse::<u8>()
-            .map_err(|e| format!("{}", e))?;
-        let s1 = str[comma + 1..second_split]
-            .parse::<u8>()
-            .map_err(|e| format!("{}", e))?;
-        let s2 = str[second_split + 1..]
-            .parse::<u8>()
-            .map_err(|e| format!("{}", e))?;
-
-        Ok(Pair {
-            first: (f1, f2),
-            second: (s1, s2),
-        })
+    fn sections(&self) -> (IntervalSet<u8>, IntervalSet<u8>) {
+        (
+            IntervalSet::from_iter([self.first]),
+            IntervalSet::from_iter([self.second]),
+        )
     }
 
     fn pair_contains(&self) -> bool {
-        let (f1, f2) = self.first;
-        let (s1, s2) = self.second;
+        let (first, second) = self.sections();
 
-        (f1 >= s1 && f2 <= s2) || (s1 >= f1 && s2 <= f2)
+        first.is_superset(&second) || second.is_superset(&first)
     }
 
     fn overlaps(&self) -> bool {
-        let (f1, f2) = self.first;
-        let (s1, s2) = self.second;
+        let (first, second) = self.sections();
 
-        (f1 >= s1 && f1 <= s2) || (s1 >= f1 && s1 <= f2)
+        !first.intersection(&second).is_empty()
     }
 }
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let parsed = lines
-        .into_iter()
-        .map(|s| Pair::from_str(&s))
-        .collect::<Result<Vec<_>, _>>()?;
+pub struct Day4;
 
-    let part1 = parsed.iter().filter(|p| p.pair_contains()).count();
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+    type Parsed = Vec<Pair>;
+    type Part1 = usize;
+    type Part2 = usize;
 
-    println!("Part 1: {}", part1);
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        parse_lines(&lines, Pair::from_str)
+    }
+
+    fn part1(&self, parsed: &Self::Parsed) -> Result<usize> {
+        Ok(parsed.iter().filter(|p| p.pair_contains()).count())
+    }
+
+    fn part2(&self, parsed: &Self::Parsed) -> Result<usize> {
+        Ok(parsed.iter().filter(|p| p.overlaps()).count())
+    }
+}
 
-    let part2 = parsed.iter().filter(|p| p.overlaps()).count();
+#[cfg(test)]
+mod test {
+    use super::Day4;
+    use crate::solution::{example, Solution};
 
-    println!("Part 2: {}", part2);
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day4, include_str!("examples/day4.txt")),
+            ("2".to_string(), "4".to_string())
+        );
+    }
 
-    Ok(())
+    #[test]
+    fn rejects_backwards_ranges() {
+        let lines = vec!["2-4,6-8".to_string(), "2-3,5-2".to_string()];
+        assert_eq!(
+            Day4.parse(lines).unwrap_err().to_string(),
+            "input line 2, column 5: invalid value: range 5-2 ends before it starts\n\
+             \x20   2-3,5-2\n\
+             \x20       ^"
+        );
+    }
 }
@@ -1,35 +1,45 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display, str::FromStr};
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
-#[derive(Debug)]
+use crate::{
+    error::{parse_lines, Error, Result},
+    solution::Solution,
+};
+
+#[derive(Debug, Clone)]
 struct Crate {
     label: char,
 }
 
-#[derive(Debug)]
-struct Stacks {
+#[derive(Debug, Clone)]
+pub struct Stacks {
     crates: HashMap<u8, Vec<Crate>>,
 }
 
 #[derive(Debug)]
-struct Instruction {
+pub struct Instruction {
     amount: u8,
     from: u8,
     to: u8,
 }
 
 impl Stacks {
-    fn from_strs(strs: &[String]) -> Result<Stacks, String> {
+    pub fn from_strs(strs: &[String]) -> Result<Stacks> {
         let mut vec = strs.iter().collect::<Vec<_>>();
 
-        let first = vec.pop().ok_or("missing header")?;
+        let first = vec
+            .pop()
+            .ok_or_else(|| Error::missing_section("stack numbers"))?;
         let columns = first
             .chars()
             .enumerate()
             .filter_map(|(i, c)| c.to_string().parse::<u8>().ok().map(|v| (i, v)))
             .collect::<Vec<_>>();
+        if columns.is_empty() {
+            return Err(Error::missing_section("stack numbers").at_line(strs.len() - 1, first));
+        }
 
         let mut crates = HashMap::new();
 
@@ -70,23 +80,24 @@ impl Stacks {
         from.and_then(|f| to.map(|t| (f, t)))
     }
 
-    fn apply_instruction(&mut self, inst: &Instruction) {
+    fn has_stack(&self, stack: u8) -> bool {
+        self.crates.contains_key(&stack)
+    }
+
+    fn apply_instruction(&mut self, inst: &Instruction) -> Result<()> {
         if let Some((from, to)) = self.get_from_to(inst) {
-            for _ in 0..inst.amount {
-                if let Some(next) = from.pop() {
-                    to.push(next)
-                }
-            }
+            let start = inst.crates_from(from.len())?;
+            to.extend(from.drain(start..).rev());
         }
+        Ok(())
     }
 
-    fn apply_instruction_9001(&mut self, inst: &Instruction) {
+    fn apply_instruction_9001(&mut self, inst: &Instruction) -> Result<()> {
         if let Some((from, to)) = self.get_from_to(inst) {
-            let mut removed = from
-                .drain((from.len() - inst.amount as usize)..)
-                .collect::<Vec<_>>();
-            to.append(&mut removed);
+            let start = inst.crates_from(from.len())?;
+            to.extend(from.drain(start..));
         }
+        Ok(())
     }
 
     fn top_stacks(&self) -> String {
@@ -101,7 +112,21 @@ impl Stacks {
 }
 
 impl Instruction {
-    fn from_str(str: &str) -> Result<Instruction, String> {
+    /// The index of the first crate moved off a stack holding `len` crates.
+    fn crates_from(&self, len: usize) -> Result<usize> {
+        len.checked_sub(self.amount as usize).ok_or_else(|| {
+            Error::unsolvable(format!(
+                "cannot move {} crates from stack {} holding {}",
+                self.amount, self.from, len
+            ))
+        })
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
         lazy_static! {
             static ref RE: Regex = Regex::new("move ([0-9]+) from ([0-9]+) to ([0-9]+)").unwrap();
         }
@@ -114,12 +139,12 @@ impl Instruction {
 
                 Some(Instruction { amount, from, to })
             })
-            .ok_or(format!("unparseable line {}", str))
+            .ok_or_else(|| Error::parse("expected \"move <n> from <stack> to <stack>\""))
     }
 }
 
-impl ToString for Stacks {
-    fn to_string(&self) -> String {
+impl Display for Stacks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut keys = self
             .crates
             .keys()
@@ -155,32 +180,90 @@ impl ToString for Stacks {
         }
 
         lines.reverse();
-        lines.join("\n")
+        write!(f, "{}", lines.join("\n"))
     }
 }
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let point = lines.partition_point(|l| !(l.is_empty() || l.starts_with("move")));
+pub struct Day5;
 
-    let mut stacks = Stacks::from_strs(&lines[..point])?;
-    let instructions = &lines[point + 1..]
-        .iter()
-        .map(|s| Instruction::from_str(s))
-        .collect::<Result<Vec<_>, _>>()?;
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+    type Parsed = (Stacks, Vec<Instruction>);
+    type Part1 = String;
+    type Part2 = String;
 
-    instructions
-        .iter()
-        .for_each(|i| stacks.apply_instruction(i));
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        let point = lines.partition_point(|l| !(l.is_empty() || l.starts_with("move")));
 
-    println!("Part 1 {}", stacks.top_stacks());
+        if point == lines.len() {
+            return Err(Error::missing_section("move instructions"));
+        }
 
-    let mut stacks = Stacks::from_strs(&lines[..point])?;
+        let stacks = Stacks::from_strs(&lines[..point])?;
+        let instructions = parse_lines(&lines[point + 1..], |l| {
+            let instruction = Instruction::from_str(l)?;
+            for stack in [instruction.from, instruction.to] {
+                if !stacks.has_stack(stack) {
+                    return Err(Error::invalid_value(format!("no stack {}", stack)));
+                }
+            }
+            Ok(instruction)
+        })
+        .map_err(|e| e.offset_line(point + 1))?;
+
+        Ok((stacks, instructions))
+    }
+
+    fn part1(&self, (stacks, instructions): &Self::Parsed) -> Result<String> {
+        let mut stacks = stacks.clone();
+
+        instructions
+            .iter()
+            .try_for_each(|i| stacks.apply_instruction(i))?;
+
+        Ok(stacks.top_stacks())
+    }
 
-    instructions
-        .iter()
-        .for_each(|i| stacks.apply_instruction_9001(i));
+    fn part2(&self, (stacks, instructions): &Self::Parsed) -> Result<String> {
+        let mut stacks = stacks.clone();
 
-    println!("Part 2 {}", stacks.top_stacks());
+        instructions
+            .iter()
+            .try_for_each(|i| stacks.apply_instruction_9001(i))?;
 
-    Ok(())
+        Ok(stacks.top_stacks())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Day5, Stacks};
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day5, include_str!("examples/day5.txt")),
+            ("CMZ".to_string(), "MCD".to_string())
+        );
+    }
+
+    #[test]
+    fn stacks() {
+        let lines: Vec<_> = ["    [D]", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 "]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let stacks = Stacks::from_strs(&lines).unwrap();
+
+        let labels =
+            |stack: u8| -> String { stacks.crates[&stack].iter().map(|c| c.label).collect() };
+        assert_eq!(labels(1), "ZN");
+        assert_eq!(labels(2), "MCD");
+        assert_eq!(labels(3), "P");
+        assert_eq!(stacks.top_stacks(), "NDP");
+
+        assert!(Stacks::from_strs(&lines[..3]).is_err());
+        assert!(Stacks::from_strs(&[]).is_err());
+    }
 }
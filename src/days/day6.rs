@@ -1,6 +1,12 @@
 use std::collections::{hash_map::RandomState, HashSet};
 
-fn markers(str: &str, size: usize) -> usize {
+use crate::{
+    error::{Error, Result},
+    solution::Solution,
+};
+
+fn markers(str: &str, size: usize) -> Result<usize> {
+    let windows = str.chars().count().saturating_sub(size - 1);
     let position = str
         .chars()
         .collect::<Vec<_>>()
@@ -12,17 +18,49 @@ fn markers(str: &str, size: usize) -> usize {
         })
         .take_while(|l| *l != size)
         .count();
-    position + size
+    if position == windows {
+        return Err(Error::unsolvable(format!(
+            "no {} distinct characters in a row",
+            size
+        )));
+    }
+    Ok(position + size)
 }
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let line = lines.first().ok_or("missing input")?;
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+    type Parsed = String;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        lines
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::missing_section("datastream"))
+    }
 
-    let part1 = markers(line, 4);
-    println!("Part 1 {}", part1);
+    fn part1(&self, line: &Self::Parsed) -> Result<usize> {
+        markers(line, 4)
+    }
+
+    fn part2(&self, line: &Self::Parsed) -> Result<usize> {
+        markers(line, 14)
+    }
+}
 
-    let part2 = markers(line, 14);
-    println!("Part 2 {}", part2);
+#[cfg(test)]
+mod test {
+    use super::Day6;
+    use crate::solution::example;
 
-    Ok(())
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day6, include_str!("examples/day6.txt")),
+            ("7".to_string(), "19".to_string())
+        );
+    }
 }
@@ -1,39 +1,55 @@
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let parsed = lines
-        .iter()
-        .map(|s| Output::from_str(s))
-        .collect::<Result<Vec<_>, _>>()?;
+use crate::{
+    error::{parse_lines, Error, Result},
+    solution::Solution,
+};
 
-    let mut fs = Fs::from_output(parsed);
+pub struct Day7;
 
-    let sized = fs.sized_dirs();
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+    type Parsed = Vec<SizedDir>;
+    type Part1 = u32;
+    type Part2 = u32;
 
-    let part1: u32 = sized
-        .iter()
-        .filter_map(|s| if s.size <= 100000 { Some(s.size) } else { None })
-        .sum();
-
-    println!("Part 1 {}", part1);
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        let mut fs = Fs::new();
+        parse_lines(&lines, |l| fs.apply(Output::from_str(l)?))?;
 
-    let size = sized.last().unwrap().size;
-    let available = 70000000 - size;
-    let to_delete = 30000000 - available;
+        Ok(fs.sized_dirs())
+    }
 
-    let part2 = sized
-        .iter()
-        .filter_map(|s| {
-            if s.size >= to_delete {
-                Some(s.size)
-            } else {
-                None
-            }
-        })
-        .min()
-        .unwrap_or_default();
+    fn part1(&self, sized: &Self::Parsed) -> Result<u32> {
+        Ok(sized
+            .iter()
+            .filter_map(|s| if s.size <= 100000 { Some(s.size) } else { None })
+            .sum())
+    }
 
-    println!("Part 2 {}", part2);
+    fn part2(&self, sized: &Self::Parsed) -> Result<u32> {
+        let size = sized
+            .last()
+            .ok_or_else(|| Error::missing_section("directories"))?
+            .size;
+        let available = 70000000u32
+            .checked_sub(size)
+            .ok_or_else(|| Error::unsolvable("files are larger than the disk"))?;
+        let to_delete = 30000000u32.saturating_sub(available);
+        if to_delete == 0 {
+            return Ok(0);
+        }
 
-    Ok(())
+        Ok(sized
+            .iter()
+            .filter_map(|s| {
+                if s.size >= to_delete {
+                    Some(s.size)
+                } else {
+                    None
+                }
+            })
+            .min()
+            .unwrap_or_default())
+    }
 }
 
 #[derive(Debug)]
@@ -55,30 +71,34 @@ enum Output {
 }
 
 impl Command {
-    fn from_str(str: &str) -> Result<Command, String> {
+    fn from_str(str: &str) -> Result<Command> {
         if let Some(dir) = str.strip_prefix("$ cd ") {
             Ok(Command::Cd(dir.to_string()))
-        } else {
+        } else if str == "$ ls" {
             Ok(Command::Ls)
+        } else {
+            Err(Error::parse("unknown command"))
         }
     }
 }
 
 impl LsOut {
-    fn from_str(str: &str) -> Result<LsOut, String> {
+    fn from_str(str: &str) -> Result<LsOut> {
         if let Some(name) = str.strip_prefix("dir ") {
             Ok(LsOut::Dir(name.to_string()))
         } else {
-            let split = str.find(' ').ok_or("could not find")?;
+            let split = str
+                .find(' ')
+                .ok_or_else(|| Error::parse("expected \"<size> <name>\""))?;
             let name = str[split + 1..].to_string();
-            let size = str[..split].parse::<u32>().map_err(|e| e.to_string())?;
+            let size = str[..split].parse::<u32>()?;
             Ok(LsOut::File(name, size))
         }
     }
 }
 
 impl Output {
-    fn from_str(str: &str) -> Result<Output, String> {
+    fn from_str(str: &str) -> Result<Output> {
         if str.starts_with('$') {
             let command = Command::from_str(str)?;
             Ok(Output::Command(command))
@@ -96,6 +116,7 @@ struct Dir {
     files: Vec<File>,
     maybe_size: Option<u32>,
 }
+#[allow(dead_code)]
 struct File(String, u32);
 
 const ROOT: usize = 0;
@@ -105,7 +126,7 @@ struct Fs {
 }
 
 #[derive(Debug)]
-struct SizedDir {
+pub struct SizedDir {
     size: u32,
 }
 
@@ -123,21 +144,22 @@ impl Fs {
         }
     }
 
-    fn cd(&mut self, dir: &str) {
+    fn cd(&mut self, dir: &str) -> Result<()> {
         if dir == "/" {
             self.pwd = ROOT;
         } else if dir == ".." {
             if let Some(p) = self.dirs[self.pwd].parent {
                 self.pwd = p
             }
-        } else if let Some(p) = self.dirs[self.pwd]
-            .sub_dirs
-            .iter()
-            .copied()
-            .find(|d| self.dirs[*d].name == dir)
-        {
-            self.pwd = p;
+        } else {
+            self.pwd = self.dirs[self.pwd]
+                .sub_dirs
+                .iter()
+                .copied()
+                .find(|d| self.dirs[*d].name == dir)
+                .ok_or_else(|| Error::invalid_value(format!("no directory {}", dir)))?;
         }
+        Ok(())
     }
 
     fn touch(&mut self, name: &str, size: u32) {
@@ -157,21 +179,18 @@ impl Fs {
         self.dirs[self.pwd].sub_dirs.push(id);
     }
 
-    fn from_output<T: IntoIterator<Item = Output>>(output: T) -> Self {
-        let mut fs = Fs::new();
-        for output in output.into_iter() {
-            match output {
-                Output::Command(Command::Ls) => (),
-                Output::Command(Command::Cd(dir)) => {
-                    fs.cd(&dir);
-                }
-                Output::Ls(LsOut::File(name, size)) => {
-                    fs.touch(&name, size);
-                }
-                Output::Ls(LsOut::Dir(name)) => fs.mkdir(&name),
+    fn apply(&mut self, output: Output) -> Result<()> {
+        match output {
+            Output::Command(Command::Ls) => (),
+            Output::Command(Command::Cd(dir)) => {
+                self.cd(&dir)?;
+            }
+            Output::Ls(LsOut::File(name, size)) => {
+                self.touch(&name, size);
             }
+            Output::Ls(LsOut::Dir(name)) => self.mkdir(&name),
         }
-        fs
+        Ok(())
     }
 
     fn sized_dirs(&mut self) -> Vec<SizedDir> {
@@ -210,3 +229,17 @@ impl Fs {
         sized
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Day7;
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day7, include_str!("examples/day7.txt")),
+            ("95437".to_string(), "24933642".to_string())
+        );
+    }
+}
@@ -1,47 +1,53 @@
-use super::point::Point2d;
-use std::collections::{hash_map::Keys, HashMap};
-
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let grid = Grid::from_lines(lines)?;
-
-    let part1 = grid.all_points().filter(|p| grid.tree_visible(p)).count();
-
-    println!("Part 1 {}", part1);
+use super::{grid::Grid, point::Point2d};
+use crate::{
+    error::{Error, Result},
+    solution::Solution,
+};
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u8 = 8;
+    type Parsed = Forest;
+    type Part1 = usize;
+    type Part2 = i32;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        Forest::from_lines(lines)
+    }
 
-    let part2 = grid
-        .all_points()
-        .map(|p| grid.visible_distance(p))
-        .max()
-        .unwrap_or_default();
+    fn part1(&self, grid: &Self::Parsed) -> Result<usize> {
+        Ok(grid.all_points().filter(|p| grid.tree_visible(p)).count())
+    }
 
-    println!("Part 2 {}", part2);
-    Ok(())
+    fn part2(&self, grid: &Self::Parsed) -> Result<i32> {
+        Ok(grid
+            .all_points()
+            .map(|p| grid.visible_distance(&p))
+            .max()
+            .unwrap_or_default())
+    }
 }
 
 #[derive(Debug)]
 struct Tree(i32);
 
-struct Grid(HashMap<Point2d, Tree>);
+pub struct Forest(Grid<Tree>);
 
-impl Grid {
-    fn from_lines(lines: Vec<String>) -> Result<Grid, String> {
-        let positions = lines
-            .iter()
-            .enumerate()
-            .flat_map(|(y, l)| l.chars().enumerate().map(move |(x, c)| (x, y, c)));
+impl Forest {
+    pub fn from_lines(lines: Vec<String>) -> Result<Forest> {
+        let grid = Grid::parse(&lines, |c| {
+            let size = c.to_digit(10).ok_or_else(|| {
+                Error::invalid_value(format!("tree height {:?} is not a digit", c))
+            })?;
+            Ok(Some(Tree(size as i32)))
+        })?;
 
-        let mut map = HashMap::new();
-
-        for (x, y, c) in positions {
-            let size = c.to_string().parse::<i32>().map_err(|e| e.to_string())?;
-            map.insert(Point2d::new(x as i32, y as i32), Tree(size));
-        }
-
-        Ok(Grid(map))
+        Ok(Forest(grid))
     }
 }
 
-fn visible<F>(grid: &HashMap<Point2d, Tree>, p: &Point2d, tree: &Tree, next: F) -> bool
+fn visible<F>(grid: &Grid<Tree>, p: &Point2d, tree: &Tree, next: F) -> bool
 where
     F: Fn(&Point2d) -> Point2d,
 {
@@ -56,7 +62,7 @@ where
     true
 }
 
-fn visible_distance<F>(grid: &HashMap<Point2d, Tree>, p: &Point2d, tree: &Tree, next: F) -> i32
+fn visible_distance<F>(grid: &Grid<Tree>, p: &Point2d, tree: &Tree, next: F) -> i32
 where
     F: Fn(&Point2d) -> Point2d,
 {
@@ -73,7 +79,7 @@ where
     distance
 }
 
-impl Grid {
+impl Forest {
     fn tree_visible(&self, p: &Point2d) -> bool {
         let grid = &self.0;
 
@@ -100,7 +106,21 @@ impl Grid {
         }
     }
 
-    fn all_points(&self) -> Keys<Point2d, Tree> {
-        self.0.keys()
+    fn all_points(&self) -> impl Iterator<Item = Point2d> + '_ {
+        self.0.points()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Day8;
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day8, include_str!("examples/day8.txt")),
+            ("21".to_string(), "8".to_string())
+        );
     }
 }
@@ -1,55 +1,68 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, str::FromStr};
 
 use super::point::Point2d;
+use crate::{
+    error::{parse_lines, Error, Result},
+    solution::Solution,
+};
 
-pub fn run(lines: Vec<String>) -> Result<(), String> {
-    let parsed = lines
-        .iter()
-        .map(|l| Move::from_str(l))
-        .collect::<Result<Vec<_>, _>>()?;
+pub struct Day9;
 
-    let mut bridge = Bridge::new(1);
-    parsed.iter().for_each(|m| {
-        bridge.do_move(m);
-    });
-    let part1 = bridge.last_tail_visited.len();
-    println!("Part 1 {}", part1);
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+    type Parsed = Vec<Move>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed> {
+        parse_lines(&lines, Move::from_str)
+    }
+
+    fn part1(&self, parsed: &Self::Parsed) -> Result<usize> {
+        Ok(tail_visits(parsed, 1))
+    }
+
+    fn part2(&self, parsed: &Self::Parsed) -> Result<usize> {
+        Ok(tail_visits(parsed, 9))
+    }
+}
 
-    let mut bridge = Bridge::new(9);
-    parsed.iter().for_each(|m| {
+fn tail_visits(moves: &[Move], length: i32) -> usize {
+    let mut bridge = Bridge::new(length);
+    moves.iter().for_each(|m| {
         bridge.do_move(m);
     });
-    let part2 = bridge.last_tail_visited.len();
-    println!("Part 2 {}", part2);
-
-    Ok(())
+    bridge.last_tail_visited.len()
 }
 
 #[derive(Debug)]
-enum Move {
+pub enum Move {
     Up(i32),
     Down(i32),
     Left(i32),
     Right(i32),
 }
 
-impl Move {
-    fn from_str(str: &str) -> Result<Move, String> {
-        if str.len() > 2 {
-            let amount = str[2..].parse::<i32>().map_err(|e| e.to_string())?;
-            match str.chars().next().unwrap() {
-                'U' => Ok(Move::Up(amount)),
-                'D' => Ok(Move::Down(amount)),
-                'L' => Ok(Move::Left(amount)),
-                'R' => Ok(Move::Right(amount)),
-
-                other => Err(format!("Unknown direction {}", other)),
-            }
-        } else {
-            Err("Too short".to_string())
+impl FromStr for Move {
+    type Err = Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let (direction, amount) = str
+            .split_once(' ')
+            .ok_or_else(|| Error::parse("expected \"<direction> <steps>\""))?;
+        let amount = amount.parse::<i32>()?;
+        match direction {
+            "U" => Ok(Move::Up(amount)),
+            "D" => Ok(Move::Down(amount)),
+            "L" => Ok(Move::Left(amount)),
+            "R" => Ok(Move::Right(amount)),
+
+            other => Err(Error::parse(format!("unknown direction {}", other))),
         }
     }
+}
 
+impl Move {
     fn amount(&self) -> i32 {
         match self {
             Move::Up(i) => *i,
@@ -122,3 +135,17 @@ impl Bridge {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Day9;
+    use crate::solution::example;
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            example(Day9, include_str!("examples/day9.txt")),
+            ("88".to_string(), "36".to_string())
+        );
+    }
+}
@@ -0,0 +1,461 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    mem,
+    sync::OnceLock,
+};
+
+use super::point::Point2d;
+use crate::error::Result;
+
+/// A rectangle of points with both corners included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Point2d,
+    pub max: Point2d,
+}
+
+impl Bounds {
+    pub fn new(min: Point2d, max: Point2d) -> Self {
+        Bounds { min, max }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.max.x() - self.min.x() + 1
+    }
+
+    pub fn height(&self) -> i32 {
+        self.max.y() - self.min.y() + 1
+    }
+
+    pub fn area(&self) -> i32 {
+        self.width() * self.height()
+    }
+
+    pub fn contains(&self, p: &Point2d) -> bool {
+        (self.min.x()..=self.max.x()).contains(&p.x())
+            && (self.min.y()..=self.max.y()).contains(&p.y())
+    }
+
+    /// Every point inside, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point2d> + '_ {
+        (self.min.y()..=self.max.y())
+            .flat_map(move |y| (self.min.x()..=self.max.x()).map(move |x| Point2d::new(x, y)))
+    }
+
+    /// Whether `p` is on one of the sides, so removing it might shrink the
+    /// bounds of what is left.
+    fn on_edge(&self, p: &Point2d) -> bool {
+        p.x() == self.min.x()
+            || p.x() == self.max.x()
+            || p.y() == self.min.y()
+            || p.y() == self.max.y()
+    }
+
+    fn including(&self, p: &Point2d) -> Self {
+        Bounds {
+            min: Point2d::new(self.min.x().min(p.x()), self.min.y().min(p.y())),
+            max: Point2d::new(self.max.x().max(p.x()), self.max.y().max(p.y())),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Storage<T> {
+    /// Every point in a fixed rectangle, row by row.
+    Dense {
+        origin: Point2d,
+        width: usize,
+        height: usize,
+        cells: Vec<Option<T>>,
+    },
+    Sparse(HashMap<Point2d, T>),
+}
+
+/// Cells laid out on a 2d plane. Grids parsed from text or created with
+/// [`Grid::dense`] are backed by a `Vec`, and fall back to a `HashMap` once
+/// something is inserted outside of that rectangle.
+#[derive(Debug, Clone)]
+pub struct Grid<T> {
+    storage: Storage<T>,
+    len: usize,
+    /// [`Grid::bounds`], worked out when first asked for and then grown on
+    /// insert, until a removal from its edge means it has to be found again.
+    bounds: OnceLock<Option<Bounds>>,
+}
+
+impl<T> Default for Grid<T> {
+    fn default() -> Self {
+        Grid::new()
+    }
+}
+
+impl<T> Grid<T> {
+    /// An empty, sparse grid.
+    pub fn new() -> Self {
+        Grid {
+            storage: Storage::Sparse(HashMap::new()),
+            len: 0,
+            bounds: OnceLock::new(),
+        }
+    }
+
+    /// An empty grid with room for every point in `bounds`.
+    pub fn dense(bounds: &Bounds) -> Self {
+        let width = bounds.width().max(0) as usize;
+        let height = bounds.height().max(0) as usize;
+        Grid {
+            storage: Storage::Dense {
+                origin: bounds.min.clone(),
+                width,
+                height,
+                cells: (0..width * height).map(|_| None).collect(),
+            },
+            len: 0,
+            bounds: OnceLock::new(),
+        }
+    }
+
+    /// Parses one cell per character with the top left character at (0, 0).
+    /// `cell` returns `None` for characters that are not part of the grid.
+    pub fn parse<F>(lines: &[String], cell: F) -> Result<Self>
+    where
+        F: FnMut(char) -> Result<Option<T>>,
+    {
+        Grid::parse_at(Point2d::new(0, 0), lines, cell)
+    }
+
+    /// Like [`Grid::parse`], with the top left character at `origin`.
+    pub fn parse_at<F>(origin: Point2d, lines: &[String], mut cell: F) -> Result<Self>
+    where
+        F: FnMut(char) -> Result<Option<T>>,
+    {
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as i32;
+        let max = origin.add_x(width - 1).add_y(lines.len() as i32 - 1);
+        let mut grid = Grid::dense(&Bounds::new(origin.clone(), max));
+
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let value = cell(c).map_err(|e| e.at_column(x + 1, line).at_line(y, line))?;
+                if let Some(value) = value {
+                    grid.insert(origin.add_x(x as i32).add_y(y as i32), value);
+                }
+            }
+        }
+
+        Ok(grid)
+    }
+
+    fn index(&self, p: &Point2d) -> Option<usize> {
+        match &self.storage {
+            Storage::Dense {
+                origin,
+                width,
+                height,
+                ..
+            } => {
+                let x = usize::try_from(p.x() - origin.x()).ok()?;
+                let y = usize::try_from(p.y() - origin.y()).ok()?;
+                (x < *width && y < *height).then_some(y * width + x)
+            }
+            Storage::Sparse(_) => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, p: &Point2d) -> Option<&T> {
+        match &self.storage {
+            Storage::Dense { cells, .. } => self.index(p).and_then(|i| cells[i].as_ref()),
+            Storage::Sparse(map) => map.get(p),
+        }
+    }
+
+    pub fn get_mut(&mut self, p: &Point2d) -> Option<&mut T> {
+        let index = self.index(p);
+        match &mut self.storage {
+            Storage::Dense { cells, .. } => index.and_then(|i| cells[i].as_mut()),
+            Storage::Sparse(map) => map.get_mut(p),
+        }
+    }
+
+    pub fn contains(&self, p: &Point2d) -> bool {
+        self.get(p).is_some()
+    }
+
+    pub fn insert(&mut self, p: Point2d, value: T) -> Option<T> {
+        if matches!(self.storage, Storage::Dense { .. }) && self.index(&p).is_none() {
+            self.make_sparse();
+        }
+
+        let grown = self.bounds.get().map(|bounds| match bounds {
+            Some(bounds) => bounds.including(&p),
+            None => Bounds::new(p.clone(), p.clone()),
+        });
+
+        let index = self.index(&p);
+        let previous = match &mut self.storage {
+            Storage::Dense { cells, .. } => {
+                cells[index.expect("inside the rectangle")].replace(value)
+            }
+            Storage::Sparse(map) => map.insert(p, value),
+        };
+        if previous.is_none() {
+            self.len += 1;
+        }
+        if let Some(grown) = grown {
+            self.bounds = OnceLock::from(Some(grown));
+        }
+        previous
+    }
+
+    pub fn remove(&mut self, p: &Point2d) -> Option<T> {
+        let index = self.index(p);
+        let removed = match &mut self.storage {
+            Storage::Dense { cells, .. } => index.and_then(|i| cells[i].take()),
+            Storage::Sparse(map) => map.remove(p),
+        };
+        if removed.is_some() {
+            self.len -= 1;
+            if let Some(Some(bounds)) = self.bounds.get() {
+                if bounds.on_edge(p) {
+                    self.bounds.take();
+                }
+            }
+        }
+        removed
+    }
+
+    fn make_sparse(&mut self) {
+        if let Storage::Dense {
+            origin,
+            width,
+            cells,
+            ..
+        } = &mut self.storage
+        {
+            let map = mem::take(cells)
+                .into_iter()
+                .enumerate()
+                .filter_map(|(i, cell)| {
+                    let p = origin.add_x((i % *width) as i32).add_y((i / *width) as i32);
+                    cell.map(|c| (p, c))
+                })
+                .collect();
+            self.storage = Storage::Sparse(map);
+        }
+    }
+
+    /// Every cell, row by row for dense grids and in no particular order for
+    /// sparse ones.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (Point2d, &T)> + '_> {
+        match &self.storage {
+            Storage::Dense {
+                origin,
+                width,
+                cells,
+                ..
+            } => Box::new(cells.iter().enumerate().filter_map(move |(i, cell)| {
+                let p = origin.add_x((i % width) as i32).add_y((i / width) as i32);
+                cell.as_ref().map(|c| (p, c))
+            })),
+            Storage::Sparse(map) => Box::new(map.iter().map(|(p, c)| (p.clone(), c))),
+        }
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Point2d> + '_ {
+        self.iter().map(|(p, _)| p)
+    }
+
+    /// The smallest rectangle containing every cell, or `None` if the grid
+    /// is empty.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+            .get_or_init(|| {
+                let mut points = self.points();
+                let first = points.next()?;
+                Some(points.fold(Bounds::new(first.clone(), first), |b, p| b.including(&p)))
+            })
+            .clone()
+    }
+
+    fn present<'a>(
+        &'a self,
+        points: impl IntoIterator<Item = Point2d> + 'a,
+    ) -> impl Iterator<Item = (Point2d, &'a T)> + 'a {
+        points
+            .into_iter()
+            .filter_map(move |p| self.get(&p).map(|c| (p, c)))
+    }
+
+    /// The cells left, right, above and below `p`.
+    pub fn neighbours(&self, p: &Point2d) -> impl Iterator<Item = (Point2d, &T)> + '_ {
+        self.present([p.left(), p.right(), p.up(), p.down()])
+    }
+
+    /// The cells surrounding `p`, including diagonally.
+    pub fn neighbours8(&self, p: &Point2d) -> impl Iterator<Item = (Point2d, &T)> + '_ {
+        self.present([
+            p.up().left(),
+            p.up(),
+            p.up().right(),
+            p.left(),
+            p.right(),
+            p.down().left(),
+            p.down(),
+            p.down().right(),
+        ])
+    }
+
+    /// The cells in row `y`, left to right. Only the first call after the
+    /// grid shrinks has to look at every cell to find its bounds.
+    pub fn row(&self, y: i32) -> impl Iterator<Item = (Point2d, &T)> + '_ {
+        let xs = self
+            .bounds()
+            .into_iter()
+            .flat_map(|b| b.min.x()..=b.max.x());
+        self.present(xs.map(move |x| Point2d::new(x, y)))
+    }
+
+    /// The cells in column `x`, top to bottom, with the same cost as
+    /// [`Grid::row`].
+    pub fn column(&self, x: i32) -> impl Iterator<Item = (Point2d, &T)> + '_ {
+        let ys = self
+            .bounds()
+            .into_iter()
+            .flat_map(|b| b.min.y()..=b.max.y());
+        self.present(ys.map(move |y| Point2d::new(x, y)))
+    }
+
+    /// Draws the grid within its bounds, one character per cell.
+    pub fn render<F>(&self, cell: F) -> Render<'_, T, F>
+    where
+        F: Fn(Option<&T>) -> char,
+    {
+        Render { grid: self, cell }
+    }
+
+    fn write_rows<F>(&self, f: &mut Formatter<'_>, mut cell: F) -> fmt::Result
+    where
+        F: FnMut(&mut Formatter<'_>, Option<&T>) -> fmt::Result,
+    {
+        if let Some(bounds) = self.bounds() {
+            for y in bounds.min.y()..=bounds.max.y() {
+                if y != bounds.min.y() {
+                    writeln!(f)?;
+                }
+                for x in bounds.min.x()..=bounds.max.x() {
+                    cell(f, self.get(&Point2d::new(x, y)))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct Render<'a, T, F> {
+    grid: &'a Grid<T>,
+    cell: F,
+}
+
+impl<T, F> Display for Render<'_, T, F>
+where
+    F: Fn(Option<&T>) -> char,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.grid
+            .write_rows(f, |f, c| write!(f, "{}", (self.cell)(c)))
+    }
+}
+
+/// Draws each cell with its own `Display`, and a space where there is none.
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_rows(f, |f, c| match c {
+            Some(c) => write!(f, "{}", c),
+            None => write!(f, " "),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Bounds, Grid};
+    use crate::{days::point::Point2d, error::Error};
+
+    fn lines(strs: &[&str]) -> Vec<String> {
+        strs.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn grid() {
+        let mut grid = Grid::parse(&lines(&["#.#", ".##"]), |c| match c {
+            '#' => Ok(Some('#')),
+            '.' => Ok(None),
+            _ => Err(Error::parse("unknown")),
+        })
+        .unwrap();
+
+        assert_eq!(grid.len(), 4);
+        assert!(grid.contains(&Point2d::new(2, 1)));
+        assert!(!grid.contains(&Point2d::new(1, 0)));
+        assert!(!grid.contains(&Point2d::new(-1, 0)));
+        assert_eq!(grid.neighbours(&Point2d::new(1, 1)).count(), 1);
+        assert_eq!(grid.neighbours8(&Point2d::new(1, 1)).count(), 3);
+        assert_eq!(
+            grid.row(1).map(|(p, _)| p.x()).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(
+            grid.column(2).map(|(p, _)| p.y()).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        assert_eq!(grid.to_string(), "# #\n ##");
+
+        grid.insert(Point2d::new(4, -1), '@');
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds::new(Point2d::new(0, -1), Point2d::new(4, 1)))
+        );
+        grid.remove(&Point2d::new(0, 0));
+        assert_eq!(grid.len(), 4);
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds::new(Point2d::new(1, -1), Point2d::new(4, 1)))
+        );
+        assert_eq!(
+            grid.render(|c| if c.is_some() { 'x' } else { '.' })
+                .to_string(),
+            "...x\n.x..\nxx.."
+        );
+        // Still spans to y = 1 and x = 1 with (1, 1) left.
+        grid.remove(&Point2d::new(2, 1));
+        assert_eq!(
+            grid.bounds(),
+            Some(Bounds::new(Point2d::new(1, -1), Point2d::new(4, 1)))
+        );
+
+        let mut empty = Grid::new();
+        assert_eq!(empty.bounds(), None);
+        empty.insert(Point2d::new(3, 3), ());
+        assert_eq!(
+            empty.bounds(),
+            Some(Bounds::new(Point2d::new(3, 3), Point2d::new(3, 3)))
+        );
+        empty.remove(&Point2d::new(3, 3));
+        assert_eq!(empty.bounds(), None);
+
+        let error = Grid::parse(&lines(&["#.", "#?"]), |c| match c {
+            '#' | '.' => Ok(Some(c)),
+            _ => Err(Error::parse("unknown")),
+        })
+        .unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(2)));
+    }
+}
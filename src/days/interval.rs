@@ -0,0 +1,281 @@
+use std::iter;
+
+use num_traits::PrimInt;
+
+/// A set of integers stored as sorted, inclusive `(start, end)` intervals
+/// that neither overlap nor touch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    intervals: Vec<(T, T)>,
+}
+
+impl<T: PrimInt> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet::new()
+    }
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet {
+            intervals: Vec::new(),
+        }
+    }
+
+    /// Adds every value from `start` to `end`, merging with any interval it
+    /// overlaps or touches. Does nothing if `start > end`.
+    pub fn insert(&mut self, start: T, end: T) {
+        if start > end {
+            return;
+        }
+
+        let one = T::one();
+        let lo = self
+            .intervals
+            .partition_point(|&(_, e)| e.saturating_add(one) < start);
+        let hi = self
+            .intervals
+            .partition_point(|&(s, _)| s <= end.saturating_add(one));
+
+        let merged = if lo < hi {
+            let (first, last) = (self.intervals[lo], self.intervals[hi - 1]);
+            (first.0.min(start), last.1.max(end))
+        } else {
+            (start, end)
+        };
+        self.intervals.splice(lo..hi, [merged]);
+    }
+
+    /// Takes out every value from `start` to `end`, splitting any interval
+    /// that sticks out on either side.
+    pub fn remove(&mut self, start: T, end: T) {
+        if start > end {
+            return;
+        }
+
+        let lo = self.intervals.partition_point(|&(_, e)| e < start);
+        let hi = self.intervals.partition_point(|&(s, _)| s <= end);
+        if lo == hi {
+            return;
+        }
+
+        let (first, last) = (self.intervals[lo], self.intervals[hi - 1]);
+        let left = (first.0 < start).then(|| (first.0, start - T::one()));
+        let right = (last.1 > end).then(|| (end + T::one(), last.1));
+        self.intervals.splice(lo..hi, left.into_iter().chain(right));
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        for &(start, end) in &other.intervals {
+            union.insert(start, end);
+        }
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (self.intervals.iter(), other.intervals.iter());
+        let (mut x, mut y) = (a.next(), b.next());
+        let mut intervals = Vec::new();
+
+        while let (Some(&(xs, xe)), Some(&(ys, ye))) = (x, y) {
+            let (start, end) = (xs.max(ys), xe.min(ye));
+            if start <= end {
+                intervals.push((start, end));
+            }
+            if xe < ye {
+                x = a.next();
+            } else {
+                y = b.next();
+            }
+        }
+
+        IntervalSet { intervals }
+    }
+
+    /// Every value from `min` to `max` that is not in the set.
+    pub fn complement(&self, min: T, max: T) -> Self {
+        IntervalSet {
+            intervals: self.gaps(min, max).collect(),
+        }
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.intervals.partition_point(|&(_, e)| e < value);
+        self.intervals.get(i).is_some_and(|&(s, _)| s <= value)
+    }
+
+    /// Whether every value in `other` is also in this set.
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.intervals.iter().all(|&(start, end)| {
+            let i = self.intervals.partition_point(|&(_, e)| e < end);
+            self.intervals.get(i).is_some_and(|&(s, _)| s <= start)
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// How many values are in the set, counted in a `u128` so that a set
+    /// spanning the whole of `T` still fits. Only a set of every `i128` or
+    /// every `u128` is too big, and comes out as `u128::MAX`.
+    pub fn len(&self) -> u128 {
+        self.intervals.iter().fold(0, |len: u128, &(start, end)| {
+            let span = match (start.to_i128(), end.to_i128()) {
+                (Some(start), Some(end)) => end.abs_diff(start),
+                // Only unsigned values can be out of range for an i128.
+                _ => end.to_u128().unwrap() - start.to_u128().unwrap(),
+            };
+            len.saturating_add(span).saturating_add(1)
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.intervals.iter().copied()
+    }
+
+    /// The runs of values from `min` to `max` that are not in the set, in
+    /// order.
+    pub fn gaps(&self, min: T, max: T) -> impl Iterator<Item = (T, T)> + '_ {
+        // The first value not yet known to be covered or reported, or `None`
+        // once past `T::max_value()`.
+        let mut next = Some(min);
+        let mut intervals = self.iter().skip_while(move |&(_, end)| end < min);
+
+        iter::from_fn(move || loop {
+            let from = next.filter(|&n| n <= max)?;
+            match intervals.next() {
+                Some((start, end)) if start <= from => next = end.checked_add(&T::one()),
+                Some((start, end)) => {
+                    next = end.checked_add(&T::one());
+                    return Some((from, (start - T::one()).min(max)));
+                }
+                None => {
+                    next = None;
+                    return Some((from, max));
+                }
+            }
+        })
+    }
+}
+
+impl<T: PrimInt> FromIterator<(T, T)> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        for (start, end) in iter {
+            set.insert(start, end);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use proptest::prelude::*;
+
+    use super::IntervalSet;
+
+    fn set(intervals: &[(i32, i32)]) -> IntervalSet<i32> {
+        intervals.iter().copied().collect()
+    }
+
+    fn values(set: &IntervalSet<i32>) -> BTreeSet<i32> {
+        set.iter().flat_map(|(start, end)| start..=end).collect()
+    }
+
+    #[test]
+    fn interval_set() {
+        let mut ranges = set(&[(10, 10)]);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges.gaps(0, 10).next(), Some((0, 9)));
+
+        ranges.insert(0, 0);
+        ranges.insert(10, 10);
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges.gaps(0, 10).collect::<Vec<_>>(), [(1, 9)]);
+
+        ranges.insert(9, 11);
+        ranges.insert(10, 12);
+        ranges.insert(8, 10);
+        assert_eq!(ranges.iter().collect::<Vec<_>>(), [(0, 0), (8, 12)]);
+
+        ranges.insert(15, 20);
+        ranges.insert(11, 17);
+        assert_eq!(ranges.len(), 14);
+        ranges.insert(1, 7);
+        assert_eq!(ranges.iter().collect::<Vec<_>>(), [(0, 20)]);
+        assert_eq!(ranges.gaps(0, 10).next(), None);
+
+        ranges.remove(5, 6);
+        ranges.remove(20, 30);
+        assert_eq!(ranges.iter().collect::<Vec<_>>(), [(0, 4), (7, 19)]);
+        assert_eq!(
+            ranges.gaps(-2, 25).collect::<Vec<_>>(),
+            [(-2, -1), (5, 6), (20, 25)]
+        );
+        assert_eq!(ranges.complement(3, 7).iter().collect::<Vec<_>>(), [(5, 6)]);
+
+        assert!(ranges.contains(4) && !ranges.contains(5));
+        assert!(ranges.is_superset(&set(&[(1, 3), (8, 19)])));
+        assert!(!ranges.is_superset(&set(&[(3, 8)])));
+
+        let full = set(&[(i32::MIN, i32::MAX)]);
+        assert_eq!(full.len(), 1 << 32);
+        assert_eq!(full.gaps(i32::MIN, i32::MAX).next(), None);
+        assert_eq!(
+            IntervalSet::from_iter([(u64::MIN, 9), (11, u64::MAX)]).len(),
+            u64::MAX as u128
+        );
+        assert_eq!(
+            IntervalSet::from_iter([(i128::MIN, i128::MAX)]).len(),
+            u128::MAX
+        );
+        assert_eq!(
+            IntervalSet::from_iter([(0, u128::MAX - 1)]).len(),
+            u128::MAX
+        );
+        assert_eq!(
+            set(&[(i32::MIN, -1)])
+                .gaps(i32::MIN, i32::MAX)
+                .collect::<Vec<_>>(),
+            [(0, i32::MAX)]
+        );
+    }
+
+    fn intervals() -> impl Strategy<Value = Vec<(i32, i32)>> {
+        prop::collection::vec((-30..30, 0..10).prop_map(|(s, l)| (s, s + l)), 0..8)
+    }
+
+    proptest! {
+        #[test]
+        fn matches_a_set_of_values(a in intervals(), b in intervals(), removed in intervals()) {
+            let (mut a, b) = (set(&a), set(&b));
+            let mut expected = values(&a);
+            for &(start, end) in &removed {
+                a.remove(start, end);
+                expected.retain(|v| !(start..=end).contains(v));
+            }
+            prop_assert_eq!(values(&a), expected.clone());
+            prop_assert_eq!(a.len(), expected.len() as u128);
+
+            let other = values(&b);
+            prop_assert_eq!(values(&a.union(&b)), &expected | &other);
+            prop_assert_eq!(values(&a.intersection(&b)), &expected & &other);
+            prop_assert_eq!(a.is_superset(&b), other.is_subset(&expected));
+
+            let all: BTreeSet<_> = (-10..=10).collect();
+            prop_assert_eq!(values(&a.complement(-10, 10)), &all - &expected);
+            for v in -40..40 {
+                prop_assert_eq!(a.contains(v), expected.contains(&v));
+            }
+
+            // The stored intervals are sorted and never touch.
+            for pair in a.intervals.windows(2) {
+                prop_assert!(pair[0].1 + 1 < pair[1].0);
+            }
+        }
+    }
+}
@@ -25,4 +25,8 @@ pub mod day23;
 pub mod day24;
 pub mod day25;
 
-mod point;
+pub mod cycle;
+pub mod grid;
+pub mod interval;
+pub mod point;
+pub mod search;
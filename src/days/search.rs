@@ -0,0 +1,213 @@
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+};
+
+/// Something that can be searched, one step at a time.
+pub trait Graph {
+    type Node: Clone + Eq + Hash;
+
+    /// The nodes one step away from `node`, with the cost of each step.
+    fn neighbours(&self, node: &Self::Node) -> Vec<(Self::Node, u32)>;
+}
+
+/// The first target a search reached.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Found<N> {
+    pub node: N,
+    pub cost: u32,
+    /// Every node from the start to `node`, if the search recorded paths.
+    pub path: Option<Vec<N>>,
+}
+
+/// Finds the cheapest way from a start node to any node matching a target.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Search {
+    paths: bool,
+}
+
+impl Search {
+    pub fn new() -> Self {
+        Search::default()
+    }
+
+    /// Also records the path taken to the target, at the cost of remembering
+    /// a parent for every node visited.
+    pub fn with_paths(mut self) -> Self {
+        self.paths = true;
+        self
+    }
+
+    /// Breadth first search, treating every step as costing one.
+    pub fn bfs<G, F>(&self, graph: &G, start: G::Node, is_target: F) -> Option<Found<G::Node>>
+    where
+        G: Graph,
+        F: Fn(&G::Node) -> bool,
+    {
+        let mut parents = HashMap::from([(start.clone(), None)]);
+        let mut queue = VecDeque::from([(start, 0)]);
+
+        while let Some((node, cost)) = queue.pop_front() {
+            if is_target(&node) {
+                return Some(self.found(node, cost, |n| parents.get(n).and_then(Option::as_ref)));
+            }
+
+            for (next, _) in graph.neighbours(&node) {
+                if let Entry::Vacant(entry) = parents.entry(next.clone()) {
+                    entry.insert(self.paths.then(|| node.clone()));
+                    queue.push_back((next, cost + 1));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Dijkstra's algorithm, using the cost of each step.
+    pub fn dijkstra<G, F>(&self, graph: &G, start: G::Node, is_target: F) -> Option<Found<G::Node>>
+    where
+        G: Graph,
+        F: Fn(&G::Node) -> bool,
+    {
+        self.astar(graph, start, is_target, |_| 0)
+    }
+
+    /// A* search. `heuristic` must never overestimate the remaining cost to
+    /// a target, or the result may not be the cheapest.
+    pub fn astar<G, F, H>(
+        &self,
+        graph: &G,
+        start: G::Node,
+        is_target: F,
+        heuristic: H,
+    ) -> Option<Found<G::Node>>
+    where
+        G: Graph,
+        F: Fn(&G::Node) -> bool,
+        H: Fn(&G::Node) -> u32,
+    {
+        let mut best = HashMap::from([(start.clone(), (0, None))]);
+        let mut queue = BinaryHeap::from([Queued {
+            estimate: heuristic(&start),
+            cost: 0,
+            node: start,
+        }]);
+
+        while let Some(Queued { cost, node, .. }) = queue.pop() {
+            if best.get(&node).is_some_and(|(c, _)| *c < cost) {
+                continue;
+            }
+
+            if is_target(&node) {
+                return Some(self.found(node, cost, |n| {
+                    best.get(n).and_then(|(_, parent)| parent.as_ref())
+                }));
+            }
+
+            for (next, step) in graph.neighbours(&node) {
+                let cost = cost + step;
+                if best.get(&next).is_some_and(|(c, _)| *c <= cost) {
+                    continue;
+                }
+
+                best.insert(next.clone(), (cost, self.paths.then(|| node.clone())));
+                queue.push(Queued {
+                    estimate: cost + heuristic(&next),
+                    cost,
+                    node: next,
+                });
+            }
+        }
+
+        None
+    }
+
+    fn found<'a, N, P>(&self, node: N, cost: u32, parent: P) -> Found<N>
+    where
+        N: Clone + 'a,
+        P: Fn(&N) -> Option<&'a N>,
+    {
+        let path = self.paths.then(|| {
+            let mut path = vec![node.clone()];
+            while let Some(p) = parent(path.last().unwrap()) {
+                path.push(p.clone());
+            }
+            path.reverse();
+            path
+        });
+
+        Found { node, cost, path }
+    }
+}
+
+/// A node waiting in the A* queue, ordered so the lowest estimate pops first.
+struct Queued<N> {
+    estimate: u32,
+    cost: u32,
+    node: N,
+}
+
+impl<N> PartialEq for Queued<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl<N> Eq for Queued<N> {}
+
+impl<N> PartialOrd for Queued<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> Ord for Queued<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.cmp(&self.estimate)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Graph, Search};
+
+    // 0 -1- 1 -1- 2 -1- 3, with a direct but expensive 0 -5- 3.
+    struct Line;
+
+    impl Graph for Line {
+        type Node = u32;
+
+        fn neighbours(&self, node: &u32) -> Vec<(u32, u32)> {
+            match node {
+                0 => vec![(1, 1), (3, 5)],
+                1 => vec![(0, 1), (2, 1)],
+                2 => vec![(1, 1), (3, 1)],
+                3 => vec![(2, 1), (0, 5)],
+                _ => vec![],
+            }
+        }
+    }
+
+    #[test]
+    fn search() {
+        let search = Search::new().with_paths();
+
+        let found = search.bfs(&Line, 0, |n| *n == 3).unwrap();
+        assert_eq!(found.cost, 1);
+        assert_eq!(found.path, Some(vec![0, 3]));
+
+        let found = search.dijkstra(&Line, 0, |n| *n == 3).unwrap();
+        assert_eq!(found.cost, 3);
+        assert_eq!(found.path, Some(vec![0, 1, 2, 3]));
+
+        let found = search.astar(&Line, 0, |n| *n == 3, |n| 3 - n).unwrap();
+        assert_eq!(found.cost, 3);
+        assert_eq!(found.path, Some(vec![0, 1, 2, 3]));
+
+        let found = Search::new().bfs(&Line, 1, |n| *n == 1).unwrap();
+        assert_eq!((found.cost, found.path), (0, None));
+
+        assert_eq!(search.bfs(&Line, 0, |n| *n == 4), None);
+    }
+}
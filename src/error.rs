@@ -0,0 +1,173 @@
+use std::{fmt::Display, num::ParseIntError};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The text does not have the expected shape.
+    Parse(String),
+    /// A part of the input that the puzzle needs is absent.
+    MissingSection(String),
+    /// The text parsed but a value in it is not allowed.
+    InvalidValue(String),
+    /// The input is well formed but has no answer.
+    Unsolvable(String),
+}
+
+/// An error from parsing or solving a day, along with where in the input it
+/// happened when that is known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub day: Option<u8>,
+    /// One based line number in the puzzle input.
+    pub line: Option<usize>,
+    /// One based column within `text`, in characters.
+    pub column: Option<usize>,
+    pub text: Option<String>,
+}
+
+impl Error {
+    fn new(kind: ErrorKind) -> Self {
+        Error {
+            kind,
+            day: None,
+            line: None,
+            column: None,
+            text: None,
+        }
+    }
+
+    pub fn parse<S: Into<String>>(message: S) -> Self {
+        Error::new(ErrorKind::Parse(message.into()))
+    }
+
+    pub fn missing_section<S: Into<String>>(section: S) -> Self {
+        Error::new(ErrorKind::MissingSection(section.into()))
+    }
+
+    pub fn invalid_value<S: Into<String>>(message: S) -> Self {
+        Error::new(ErrorKind::InvalidValue(message.into()))
+    }
+
+    pub fn unsolvable<S: Into<String>>(message: S) -> Self {
+        Error::new(ErrorKind::Unsolvable(message.into()))
+    }
+
+    /// Records the offending text, unless a more specific one is already set.
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.text.get_or_insert_with(|| text.to_string());
+        self
+    }
+
+    /// Records the zero based `index` of the line that failed, unless an
+    /// inner parser already knew better.
+    pub fn at_line(mut self, index: usize, text: &str) -> Self {
+        self.line.get_or_insert(index + 1);
+        self.with_text(text)
+    }
+
+    /// Records the one based `column` within `text` where parsing failed.
+    pub fn at_column(mut self, column: usize, text: &str) -> Self {
+        self.column.get_or_insert(column);
+        self.with_text(text)
+    }
+
+    /// Shifts the line number by `offset` lines, for errors from parsers that
+    /// only saw part of the input.
+    pub fn offset_line(mut self, offset: usize) -> Self {
+        self.line = self.line.map(|l| l + offset);
+        self
+    }
+
+    pub fn for_day(mut self, day: u8) -> Self {
+        self.day.get_or_insert(day);
+        self
+    }
+}
+
+/// Parses every line with `parse`, attaching the line number and text to
+/// the first error.
+pub fn parse_lines<T, F>(lines: &[String], mut parse: F) -> Result<Vec<T>>
+where
+    F: FnMut(&str) -> Result<T>,
+{
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, l)| parse(l).map_err(|e| e.at_line(i, l)))
+        .collect()
+}
+
+impl From<ParseIntError> for Error {
+    fn from(error: ParseIntError) -> Self {
+        Error::parse(format!("expected integer: {}", error))
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::Parse(message) => write!(f, "parse error: {}", message),
+            ErrorKind::MissingSection(section) => write!(f, "missing {}", section),
+            ErrorKind::InvalidValue(message) => write!(f, "invalid value: {}", message),
+            ErrorKind::Unsolvable(message) => write!(f, "unsolvable input: {}", message),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day{} ", day)?;
+        }
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "input line {}, column {}: ", line, column)?,
+            (Some(line), None) => write!(f, "input line {}: ", line)?,
+            (None, Some(column)) => write!(f, "column {}: ", column)?,
+            (None, None) if self.day.is_some() => write!(f, "input: ")?,
+            (None, None) => (),
+        }
+        write!(f, "{}", self.kind)?;
+        if let Some(text) = &self.text {
+            write!(f, "\n    {}", text)?;
+            if let Some(column) = self.column {
+                write!(f, "\n    {:>width$}", "^", width = column)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_lines, Error};
+
+    #[test]
+    fn error() {
+        let lines: Vec<_> = ["1", "2", "x"].into_iter().map(String::from).collect();
+        let error = parse_lines(&lines, |l| Ok(l.parse::<i32>()?)).unwrap_err();
+
+        assert_eq!(error.line, Some(3));
+        assert_eq!(error.text.as_deref(), Some("x"));
+        assert_eq!(
+            error.for_day(1).to_string(),
+            "day1 input line 3: parse error: expected integer: invalid digit found in string\n    x"
+        );
+
+        let error = Error::parse("expected integer")
+            .at_column(19, "  Starting items: x")
+            .at_line(13, "ignored")
+            .for_day(11);
+        assert_eq!(
+            error.to_string(),
+            "day11 input line 14, column 19: parse error: expected integer\n    \
+             \x20 Starting items: x\n                      ^"
+        );
+
+        let error = Error::missing_section("path").for_day(22);
+        assert_eq!(error.to_string(), "day22 input: missing path");
+    }
+}
@@ -0,0 +1,54 @@
+use std::{
+    convert::Infallible,
+    fmt::Display,
+    fs,
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Where a day's puzzle input is read from. On the command line `-` means
+/// stdin and anything else is a file path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    Stdin,
+    File(PathBuf),
+}
+
+impl InputSource {
+    /// The conventional `day{n}.txt` file inside `dir`.
+    pub fn for_day<P: AsRef<Path>>(dir: P, day: i32) -> Self {
+        InputSource::File(dir.as_ref().join(format!("day{}.txt", day)))
+    }
+
+    pub fn read_lines(&self) -> Result<Vec<String>, String> {
+        match self {
+            InputSource::Stdin => io::stdin().lock().lines().collect(),
+            InputSource::File(path) => {
+                fs::File::open(path).and_then(|file| BufReader::new(file).lines().collect())
+            }
+        }
+        .map_err(|e| format!("{}: {}", self, e))
+    }
+}
+
+impl FromStr for InputSource {
+    type Err = Infallible;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        if str == "-" {
+            Ok(InputSource::Stdin)
+        } else {
+            Ok(InputSource::File(PathBuf::from(str)))
+        }
+    }
+}
+
+impl Display for InputSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputSource::Stdin => write!(f, "stdin"),
+            InputSource::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! Advent of Code 2022 solutions.
+//!
+//! Every day implements [`Solution`], and [`programs`] returns all of them
+//! keyed by day number.
+
+use std::collections::BTreeMap;
+
+pub mod check;
+pub mod days;
+pub mod error;
+pub mod input;
+pub mod params;
+pub mod scanner;
+pub mod selection;
+pub mod solution;
+pub mod visualize;
+
+pub use days::point::Point2d;
+pub use error::{Error, ErrorKind};
+pub use input::InputSource;
+pub use params::Params;
+pub use selection::DaySelection;
+pub use solution::{Answers, Export, Program, Solution, Timings};
+
+use days::*;
+
+pub type Programs = BTreeMap<i32, Box<dyn Program>>;
+
+pub fn programs() -> Programs {
+    let mut programs: Programs = BTreeMap::new();
+    programs.insert(1, Box::new(day1::Day1));
+    programs.insert(2, Box::new(day2::Day2));
+    programs.insert(3, Box::new(day3::Day3));
+    programs.insert(4, Box::new(day4::Day4));
+    programs.insert(5, Box::new(day5::Day5));
+    programs.insert(6, Box::new(day6::Day6));
+    programs.insert(7, Box::new(day7::Day7));
+    programs.insert(8, Box::new(day8::Day8));
+    programs.insert(9, Box::new(day9::Day9));
+    programs.insert(10, Box::new(day10::Day10));
+    programs.insert(11, Box::new(day11::Day11));
+    programs.insert(12, Box::new(day12::Day12));
+    programs.insert(13, Box::new(day13::Day13));
+    programs.insert(14, Box::new(day14::Day14));
+    programs.insert(15, Box::new(day15::Day15));
+    programs.insert(16, Box::new(day16::Day16));
+    programs.insert(17, Box::new(day17::Day17));
+    programs.insert(18, Box::new(day18::Day18));
+    programs.insert(19, Box::new(day19::Day19));
+    programs.insert(20, Box::new(day20::Day20));
+    programs.insert(21, Box::new(day21::Day21));
+    programs.insert(22, Box::new(day22::Day22));
+    programs.insert(23, Box::new(day23::Day23));
+    programs.insert(24, Box::new(day24::Day24));
+    programs.insert(25, Box::new(day25::Day25));
+    programs
+}
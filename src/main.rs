@@ -1,64 +1,490 @@
 use std::{
-    collections::HashMap,
-    fmt::Debug,
-    fs,
-    io::{BufRead, BufReader},
+    fmt::Display,
+    num::NonZeroU32,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
     process::exit,
+    time::Duration,
 };
 
-use structopt::StructOpt;
-
-mod days;
+use structopt::{
+    clap::{self, AppSettings, ErrorKind},
+    StructOpt,
+};
 
-use days::*;
+use aoc22::{
+    check::{Expected, Status},
+    params::parse_param,
+    programs,
+    visualize::{Animation, Viewport},
+    Answers, DaySelection, Export, InputSource, Params, Programs, Timings,
+};
 
 #[derive(StructOpt)]
+#[structopt(setting = AppSettings::SubcommandsNegateReqs, setting = AppSettings::ArgsNegateSubcommands)]
 struct Opt {
-    #[structopt(name = "day")]
+    /// A day, `all`, a range like `1..=10` or a list like `3,7,22`
+    #[structopt(name = "days")]
+    days: Option<DaySelection>,
+
+    /// Read the input from this file instead, or from stdin if `-`
+    #[structopt(short, long, global = true)]
+    input: Option<InputSource>,
+
+    /// Directory containing the `dayN.txt` input files
+    #[structopt(long, env = "AOC_INPUT_DIR", default_value = "input", global = true)]
+    input_dir: PathBuf,
+
+    /// A setting for the days that take it, like `-p width=9`
+    #[structopt(
+        short = "p",
+        long = "param",
+        parse(try_from_str = parse_param),
+        number_of_values = 1,
+        global = true
+    )]
+    params: Vec<(String, String)>,
+
+    /// Compare the answers against those recorded in the answers directory
+    #[structopt(long, conflicts_with_all = &["export", "visualize"])]
+    check: bool,
+
+    /// Directory containing the recorded `dayN.txt` answers
+    #[structopt(long, default_value = "answers")]
+    answers_dir: PathBuf,
+
+    /// Report how long parsing and each part took
+    #[structopt(long, conflicts_with_all = &["export", "visualize"])]
+    time: bool,
+
+    /// Print the parsed input in this format instead of solving it
+    #[structopt(long, possible_values = &Export::VARIANTS)]
+    export: Option<Export>,
+
+    /// Animate the simulation behind the answers instead of solving it
+    #[structopt(long, conflicts_with = "export")]
+    visualize: bool,
+
+    /// Frames per second for --visualize
+    #[structopt(long, default_value = "10")]
+    fps: NonZeroU32,
+
+    /// Only show this much of each --visualize frame, like `80x24`
+    #[structopt(long)]
+    viewport: Option<Viewport>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    /// Run each day repeatedly and report the min, median and max timings
+    Bench {
+        /// A day, `all`, a range like `1..=10` or a list like `3,7,22`
+        #[structopt(name = "days")]
+        days: DaySelection,
+
+        /// How many times to run each day
+        #[structopt(short = "n", long, default_value = "10")]
+        iterations: usize,
+    },
+}
+
+struct DayResult {
     day: i32,
+    answers: Result<Answers, String>,
+    timings: Timings,
+    checks: Option<[Status; 2]>,
 }
 
-fn default_error_handler<E: Debug, R>(error: E) -> R {
-    println!("{:#?}", error);
+impl DayResult {
+    fn failed(&self) -> bool {
+        self.answers.is_err()
+            || self
+                .checks
+                .iter()
+                .flatten()
+                .any(|s| matches!(s, Status::Fail(_)))
+    }
+}
+
+fn default_error_handler<E: Display, R>(error: E) -> R {
+    println!("{}", error);
     exit(1);
 }
 
-type Programs = HashMap<i32, Box<dyn Fn(Vec<String>) -> Result<(), String>>>;
+fn print_part(part: i32, answer: &str, check: Option<&Status>) {
+    let check = check.map(|c| format!(" {}", c)).unwrap_or_default();
+    if answer.contains('\n') {
+        println!("Part {}{}\n{}", part, check, answer);
+    } else {
+        println!("Part {} {}{}", part, answer, check);
+    }
+}
+
+fn format_duration(duration: &Duration) -> String {
+    format!("{:.2?}", duration)
+}
+
+/// Fails if some `-p` key is not taken by any of `days`, so that a typo is
+/// not silently ignored.
+fn check_params(programs: &Programs, days: &[i32], params: &Params) -> Result<(), String> {
+    let taken: Vec<_> = days
+        .iter()
+        .filter_map(|day| Some((day, programs.get(day)?.params())))
+        .filter(|(_, keys)| !keys.is_empty())
+        .collect();
+
+    match params
+        .keys()
+        .find(|key| !taken.iter().any(|(_, keys)| keys.contains(key)))
+    {
+        Some(key) if taken.is_empty() => Err(format!(
+            "unknown parameter {}, none of the selected days take any",
+            key
+        )),
+        Some(key) => {
+            let taken: Vec<_> = taken
+                .iter()
+                .map(|(day, keys)| format!("day {} takes {}", day, keys.join(", ")))
+                .collect();
+            Err(format!("unknown parameter {}; {}", key, taken.join("; ")))
+        }
+        None => Ok(()),
+    }
+}
+
+fn run_day(
+    programs: &Programs,
+    day: i32,
+    lines: Vec<String>,
+    params: &Params,
+) -> Result<(Answers, Timings), String> {
+    let program = programs
+        .get(&day)
+        .ok_or_else(|| format!("Undefined day: {}", day))?;
+
+    let params = params.only(program.params());
+    panic::catch_unwind(AssertUnwindSafe(|| program.solve_timed(lines, &params)))
+        .map(|result| result.map_err(|e| e.to_string()))
+        .unwrap_or_else(|e| {
+            let message = e
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| e.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            Err(format!("panicked: {}", message))
+        })
+}
+
+fn print_table(header: &[String], rows: &[Vec<String>]) {
+    let mut widths = vec![0; header.len()];
+    for row in rows.iter().map(|r| &r[..]).chain([header]) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = cell.lines().map(|l| l.len()).fold(*width, usize::max);
+        }
+    }
+
+    let print_row = |row: &[String]| {
+        let lines: Vec<Vec<_>> = row.iter().map(|c| c.lines().collect()).collect();
+        let height = lines
+            .iter()
+            .map(|l| l.len())
+            .max()
+            .unwrap_or_default()
+            .max(1);
+        for i in 0..height {
+            let cells: Vec<_> = lines
+                .iter()
+                .zip(&widths)
+                .map(|(l, w)| format!("{:<w$}", l.get(i).unwrap_or(&""), w = w))
+                .collect();
+            println!("{}", cells.join(" | ").trim_end());
+        }
+    };
+
+    print_row(header);
+    println!(
+        "{}",
+        widths
+            .iter()
+            .map(|w| "-".repeat(*w))
+            .collect::<Vec<_>>()
+            .join("-+-")
+    );
+    rows.iter().for_each(|r| print_row(r));
+}
+
+fn print_summary(results: &[DayResult], check: bool, time: bool) {
+    let mut header = vec!["Day", "Part 1"];
+    if check {
+        header.push("Check");
+    }
+    header.push("Part 2");
+    if check {
+        header.push("Check");
+    }
+    if time {
+        header.extend(["Parse", "Part 1 time", "Part 2 time"]);
+    }
+    let header: Vec<_> = header.into_iter().map(|h| h.to_string()).collect();
+
+    let rows: Vec<_> = results
+        .iter()
+        .map(|result| {
+            let mut row = vec![result.day.to_string()];
+            match &result.answers {
+                Ok(answers) => {
+                    for (i, answer) in [&answers.part1, &answers.part2].into_iter().enumerate() {
+                        row.push(answer.clone());
+                        if let Some(checks) = &result.checks {
+                            row.push(checks[i].to_string());
+                        }
+                    }
+                    if time {
+                        let Timings {
+                            parse,
+                            part1,
+                            part2,
+                        } = &result.timings;
+                        row.extend([parse, part1, part2].map(format_duration));
+                    }
+                }
+                Err(e) => row.push(format!("error: {}", e)),
+            }
+            row.resize(header.len(), String::new());
+            row
+        })
+        .collect();
+
+    print_table(&header, &rows);
+}
+
+/// The details of each day that has some, below the summary table.
+fn print_details(results: &[DayResult]) {
+    for result in results {
+        if let Ok(answers) = &result.answers {
+            if !answers.details.is_empty() {
+                println!("\nDay {}\n{}", result.day, answers.details.join("\n"));
+            }
+        }
+    }
+}
+
+fn run(opt: &Opt, days: Vec<i32>, params: &Params, input: impl Fn(i32) -> InputSource) {
+    let programs = programs();
+
+    let run = |day| {
+        let result = input(day)
+            .read_lines()
+            .and_then(|lines| run_day(&programs, day, lines, params));
+        let (answers, timings) = match result {
+            Ok((answers, timings)) => (Ok(answers), timings),
+            Err(e) => (Err(e), Timings::default()),
+        };
+        let checks = match &answers {
+            Ok(answers) if opt.check => match Expected::load(&opt.answers_dir, day) {
+                Ok(expected) => Some(expected.check(answers)),
+                Err(e) => {
+                    return DayResult {
+                        day,
+                        answers: Err(e),
+                        timings,
+                        checks: None,
+                    }
+                }
+            },
+            _ => None,
+        };
+        DayResult {
+            day,
+            answers,
+            timings,
+            checks,
+        }
+    };
+
+    let results: Vec<_> = days.into_iter().map(run).collect();
+
+    if let [result] = &results[..] {
+        let answers = result
+            .answers
+            .as_ref()
+            .unwrap_or_else(default_error_handler);
+        let checks = result.checks.as_ref();
+
+        print_part(1, &answers.part1, checks.map(|c| &c[0]));
+        print_part(2, &answers.part2, checks.map(|c| &c[1]));
+        for line in &answers.details {
+            println!("{}", line);
+        }
+
+        if opt.time {
+            let Timings {
+                parse,
+                part1,
+                part2,
+            } = &result.timings;
+            println!(
+                "Parse {}, Part 1 {}, Part 2 {}",
+                format_duration(parse),
+                format_duration(part1),
+                format_duration(part2)
+            );
+        }
+    } else {
+        print_summary(&results, opt.check, opt.time);
+        print_details(&results);
+    }
+
+    if results.iter().any(|r| r.failed()) {
+        exit(1);
+    }
+}
+
+fn bench(days: Vec<i32>, iterations: usize, params: &Params, input: impl Fn(i32) -> InputSource) {
+    let programs = programs();
+    let iterations = iterations.max(1);
+
+    let header: Vec<_> = ["Day", "Phase", "Min", "Median", "Max"]
+        .into_iter()
+        .map(|h| h.to_string())
+        .collect();
+    let mut rows = Vec::new();
+    let mut failed = false;
+
+    for day in days {
+        let timings = input(day).read_lines().and_then(|lines| {
+            (0..iterations)
+                .map(|_| run_day(&programs, day, lines.clone(), params).map(|(_, t)| t))
+                .collect::<Result<Vec<_>, _>>()
+        });
+
+        match timings {
+            Ok(timings) => {
+                let phases = [
+                    ("Parse", timings.iter().map(|t| t.parse).collect::<Vec<_>>()),
+                    ("Part 1", timings.iter().map(|t| t.part1).collect()),
+                    ("Part 2", timings.iter().map(|t| t.part2).collect()),
+                ];
+                for (phase, mut durations) in phases {
+                    durations.sort();
+
+                    rows.push(vec![
+                        day.to_string(),
+                        phase.to_string(),
+                        format_duration(&durations[0]),
+                        format_duration(&durations[durations.len() / 2]),
+                        format_duration(&durations[durations.len() - 1]),
+                    ]);
+                }
+            }
+            Err(e) => {
+                failed = true;
+                rows.push(vec![
+                    day.to_string(),
+                    format!("error: {}", e),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                ]);
+            }
+        }
+    }
+
+    print_table(&header, &rows);
+
+    if failed {
+        exit(1);
+    }
+}
+
+fn export(days: Vec<i32>, format: Export, params: &Params, input: impl Fn(i32) -> InputSource) {
+    let programs = programs();
+
+    for day in days {
+        let output = input(day).read_lines().and_then(|lines| {
+            let program = programs
+                .get(&day)
+                .ok_or_else(|| format!("Undefined day: {}", day))?;
+            program
+                .export(lines, &params.only(program.params()), format)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("day {} has no {} export", day, format))
+        });
+
+        println!("{}", output.unwrap_or_else(default_error_handler));
+    }
+}
+
+fn visualize(
+    days: Vec<i32>,
+    animation: Animation,
+    params: &Params,
+    input: impl Fn(i32) -> InputSource,
+) {
+    let programs = programs();
+
+    for day in days {
+        let result = input(day).read_lines().and_then(|lines| {
+            let program = programs
+                .get(&day)
+                .ok_or_else(|| format!("Undefined day: {}", day))?;
+            program
+                .visualize(lines, &params.only(program.params()), &mut |frame| {
+                    animation.show(&frame)
+                })
+                .map_err(|e| e.to_string())?
+                .then_some(())
+                .ok_or_else(|| format!("day {} has nothing to visualize", day))
+        });
+
+        result.unwrap_or_else(default_error_handler);
+    }
+}
 
 fn main() {
     let opt = Opt::from_args();
-    let mut programs: Programs = HashMap::new();
-    programs.insert(1, Box::new(day1::run));
-    programs.insert(2, Box::new(day2::run));
-    programs.insert(3, Box::new(day3::run));
-    programs.insert(4, Box::new(day4::run));
-    programs.insert(5, Box::new(day5::run));
-    programs.insert(6, Box::new(day6::run));
-    programs.insert(7, Box::new(day7::run));
-    programs.insert(8, Box::new(day8::run));
-    programs.insert(9, Box::new(day9::run));
-    programs.insert(10, Box::new(day10::run));
-    programs.insert(11, Box::new(day11::run));
-    programs.insert(12, Box::new(day12::run));
-    programs.insert(13, Box::new(day13::run));
-    programs.insert(14, Box::new(day14::run));
-    programs.insert(15, Box::new(day15::run));
-    programs.insert(16, Box::new(day16::run));
-    programs.insert(17, Box::new(day17::run));
-    programs.insert(18, Box::new(day18::run));
-    programs.insert(19, Box::new(day19::run));
-    programs.insert(20, Box::new(day20::run));
-    programs.insert(21, Box::new(day21::run));
-    programs.insert(22, Box::new(day22::run));
-    programs.insert(23, Box::new(day23::run));
-    programs.insert(24, Box::new(day24::run));
-    programs.insert(25, Box::new(day25::run));
+    let available: Vec<_> = programs().keys().copied().collect();
 
-    let program = programs
-        .get(&opt.day)
-        .unwrap_or_else(|| default_error_handler(format!("Undefined day: {}", opt.day).as_str()));
-    let file_contents: Vec<String> = fs::File::open(format!("input/day{}.txt", opt.day))
-        .and_then(|file| BufReader::new(file).lines().collect())
-        .unwrap_or_else(default_error_handler);
-    program(file_contents).unwrap_or_else(default_error_handler)
+    let selection = match &opt.command {
+        Some(Command::Bench { days, .. }) => days,
+        None => opt.days.as_ref().unwrap_or_else(|| {
+            clap::Error::with_description(
+                "The following required arguments were not provided: <days>",
+                ErrorKind::MissingRequiredArgument,
+            )
+            .exit()
+        }),
+    };
+    let days = selection.resolve(available);
+
+    if opt.input.is_some() && days.len() != 1 {
+        default_error_handler("--input can only be used with a single day")
+    }
+
+    let input = |day| {
+        opt.input
+            .clone()
+            .unwrap_or_else(|| InputSource::for_day(&opt.input_dir, day))
+    };
+
+    let params: Params = opt.params.iter().cloned().collect();
+    check_params(&programs(), &days, &params).unwrap_or_else(default_error_handler);
+
+    match &opt.command {
+        Some(Command::Bench { iterations, .. }) => bench(days, *iterations, &params, input),
+        None if opt.visualize => {
+            let animation = Animation {
+                fps: opt.fps,
+                viewport: opt.viewport,
+            };
+            visualize(days, animation, &params, input)
+        }
+        None => match opt.export {
+            Some(format) => export(days, format, &params, input),
+            None => run(&opt, days, &params, input),
+        },
+    }
 }
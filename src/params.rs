@@ -0,0 +1,127 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use crate::error::{Error, Result};
+
+/// Extra settings for a day, given on the command line as `-p key=value`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Params(BTreeMap<String, String>);
+
+impl Params {
+    pub fn new() -> Self {
+        Params::default()
+    }
+
+    pub fn insert(&mut self, key: &str, value: &str) {
+        self.0.insert(key.to_string(), value.to_string());
+    }
+
+    /// The value of `key` parsed as a `T`, if it was given.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.0
+            .get(key)
+            .map(|value| {
+                value.parse().map_err(|e| {
+                    Error::invalid_value(format!("parameter {}={}: {}", key, value, e))
+                })
+            })
+            .transpose()
+    }
+
+    /// The value of `key` parsed as a `T`, or `default` if it was not given.
+    pub fn get_or<T>(&self, key: &str, default: T) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        Ok(self.get(key)?.unwrap_or(default))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// Just the settings whose key is one of `keys`.
+    pub fn only(&self, keys: &[&str]) -> Params {
+        self.0
+            .iter()
+            .filter(|(key, _)| keys.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Fails on the first key that is not one of `accepted`.
+    pub fn check_keys(&self, accepted: &[&str]) -> Result<()> {
+        let Some(key) = self.keys().find(|key| !accepted.contains(key)) else {
+            return Ok(());
+        };
+
+        let expected = if accepted.is_empty() {
+            "this day takes none".to_string()
+        } else {
+            format!("expected one of {}", accepted.join(", "))
+        };
+        Err(Error::invalid_value(format!(
+            "unknown parameter {}, {}",
+            key, expected
+        )))
+    }
+}
+
+impl FromIterator<(String, String)> for Params {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Params(iter.into_iter().collect())
+    }
+}
+
+/// Splits a `key=value` command line argument.
+pub fn parse_param(str: &str) -> Result<(String, String), String> {
+    match str.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected key=value, got '{}'", str)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_param, Params};
+
+    #[test]
+    fn params() {
+        let params: Params = ["width=9", "rocks=a=b.txt"]
+            .into_iter()
+            .map(|p| parse_param(p).unwrap())
+            .collect();
+
+        assert_eq!(params.get::<usize>("width"), Ok(Some(9)));
+        assert_eq!(
+            params.get_or("rocks", String::new()),
+            Ok("a=b.txt".to_string())
+        );
+        assert_eq!(params.get_or("spawn_x", 2), Ok(2));
+        assert_eq!(
+            params.get::<i32>("rocks").unwrap_err().to_string(),
+            "invalid value: parameter rocks=a=b.txt: invalid digit found in string"
+        );
+
+        assert_eq!(params.check_keys(&["rocks", "width", "spawn_x"]), Ok(()));
+        assert_eq!(
+            params.check_keys(&["width"]).unwrap_err().to_string(),
+            "invalid value: unknown parameter rocks, expected one of width"
+        );
+        assert_eq!(
+            params.check_keys(&[]).unwrap_err().to_string(),
+            "invalid value: unknown parameter rocks, this day takes none"
+        );
+        assert_eq!(
+            params.only(&["width", "row"]).keys().collect::<Vec<_>>(),
+            ["width"]
+        );
+
+        assert!(parse_param("width").is_err());
+        assert!(parse_param("=9").is_err());
+    }
+}
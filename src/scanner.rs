@@ -0,0 +1,165 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::error::{Error, Result};
+
+/// Walks along a single line of input, keeping track of the column so that
+/// errors can point at the character that was not expected.
+#[derive(Clone)]
+pub struct Scanner<'a> {
+    line: &'a str,
+    position: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(line: &'a str) -> Self {
+        Scanner { line, position: 0 }
+    }
+
+    /// Runs `parse` over the whole of `line`, failing if anything is left
+    /// over afterwards.
+    pub fn parse<T, F>(line: &'a str, parse: F) -> Result<T>
+    where
+        F: FnOnce(&mut Scanner<'a>) -> Result<T>,
+    {
+        let mut scanner = Scanner::new(line);
+        let parsed = parse(&mut scanner)?;
+        scanner.end()?;
+        Ok(parsed)
+    }
+
+    /// Like [`Scanner::parse`] for line `index` of `lines`, recording the
+    /// line number in any error.
+    pub fn parse_line<T, F>(lines: &'a [String], index: usize, parse: F) -> Result<T>
+    where
+        F: FnOnce(&mut Scanner<'a>) -> Result<T>,
+    {
+        Scanner::parse(&lines[index], parse).map_err(|e| e.at_line(index, &lines[index]))
+    }
+
+    /// One based column of the next character.
+    pub fn column(&self) -> usize {
+        self.line[..self.position].chars().count() + 1
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.line[self.position..]
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    /// Points `error` at the next character.
+    pub fn error(&self, error: Error) -> Error {
+        error.at_column(self.column(), self.line)
+    }
+
+    /// Consumes `literal` if the rest of the line starts with it.
+    pub fn eat(&mut self, literal: &str) -> bool {
+        if self.rest().starts_with(literal) {
+            self.position += literal.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes `literal`, or points at the first character that differs.
+    pub fn literal(&mut self, literal: &str) -> Result<()> {
+        if self.eat(literal) {
+            return Ok(());
+        }
+
+        let matching: usize = self
+            .rest()
+            .chars()
+            .zip(literal.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        self.position += matching;
+        let error = self.error(Error::parse(format!("expected {:?}", literal)));
+        self.position -= matching;
+
+        Err(error)
+    }
+
+    pub fn take_while<F>(&mut self, predicate: F) -> &'a str
+    where
+        F: Fn(char) -> bool,
+    {
+        let rest = self.rest();
+        let len = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.position += len;
+        &rest[..len]
+    }
+
+    /// Consumes an optionally negative run of digits.
+    pub fn integer<T>(&mut self) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let start = self.position;
+        self.eat("-");
+        if self.take_while(|c| c.is_ascii_digit()).is_empty() {
+            self.position = start;
+            return Err(self.error(Error::parse("expected integer")));
+        }
+
+        self.line[start..self.position].parse().map_err(|e| {
+            let column = self.line[..start].chars().count() + 1;
+            Error::invalid_value(format!("{}", e)).at_column(column, self.line)
+        })
+    }
+
+    /// Parses one or more items with `item`, separated by `separator`.
+    pub fn separated<T, F>(&mut self, separator: &str, mut item: F) -> Result<Vec<T>>
+    where
+        F: FnMut(&mut Self) -> Result<T>,
+    {
+        let mut items = vec![item(self)?];
+        while self.eat(separator) {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    /// Fails if anything is left on the line.
+    pub fn end(&self) -> Result<()> {
+        match self.rest().chars().next() {
+            None => Ok(()),
+            Some(c) => Err(self.error(Error::parse(format!("unexpected {:?}", c)))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Scanner;
+
+    #[test]
+    fn scanner() {
+        let items = Scanner::parse("items: 79, -98", |s| {
+            s.literal("items: ")?;
+            s.separated(", ", Scanner::integer::<i32>)
+        });
+        assert_eq!(items, Ok(vec![79, -98]));
+
+        let error = Scanner::parse("items: 79, x8", |s| {
+            s.literal("items: ")?;
+            s.separated(", ", Scanner::integer::<i32>)
+        })
+        .unwrap_err();
+        assert_eq!(error.column, Some(12));
+
+        let error = Scanner::parse("item: 79", |s| s.literal("items: ")).unwrap_err();
+        assert_eq!(error.column, Some(5));
+
+        let error = Scanner::parse("300", |s| s.integer::<u8>()).unwrap_err();
+        assert_eq!(error.column, Some(1));
+
+        let error = Scanner::parse("1,2", |s| s.integer::<u8>()).unwrap_err();
+        assert_eq!(error.column, Some(2));
+    }
+}
@@ -0,0 +1,90 @@
+use std::{collections::BTreeSet, str::FromStr};
+
+/// Which days to run, as given on the command line: `all`, a single day, a
+/// range such as `1..=10` or `1..11`, or a comma separated list of any of
+/// these like `3,7,20..=22`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DaySelection {
+    All,
+    Days(Vec<i32>),
+}
+
+impl DaySelection {
+    /// The selected days in order, with `all` resolved against the days that
+    /// are actually available.
+    pub fn resolve<I: IntoIterator<Item = i32>>(&self, available: I) -> Vec<i32> {
+        match self {
+            DaySelection::All => {
+                let mut days: Vec<_> = available.into_iter().collect();
+                days.sort();
+                days
+            }
+            DaySelection::Days(days) => days.clone(),
+        }
+    }
+}
+
+fn parse_day(str: &str) -> Result<i32, String> {
+    str.trim()
+        .parse::<i32>()
+        .map_err(|e| format!("invalid day '{}': {}", str, e))
+}
+
+impl FromStr for DaySelection {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        if str.trim() == "all" {
+            return Ok(DaySelection::All);
+        }
+
+        let mut days = BTreeSet::new();
+        for part in str.split(',') {
+            if let Some((from, to)) = part.split_once("..=") {
+                let (from, to) = (parse_day(from)?, parse_day(to)?);
+                if from > to {
+                    return Err(format!("empty range '{}'", part));
+                }
+                days.extend(from..=to);
+            } else if let Some((from, to)) = part.split_once("..") {
+                let (from, to) = (parse_day(from)?, parse_day(to)?);
+                if from >= to {
+                    return Err(format!("empty range '{}'", part));
+                }
+                days.extend(from..to);
+            } else {
+                days.insert(parse_day(part)?);
+            }
+        }
+
+        Ok(DaySelection::Days(days.into_iter().collect()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DaySelection;
+
+    #[test]
+    fn selection() {
+        assert_eq!("all".parse(), Ok(DaySelection::All));
+        assert_eq!("7".parse(), Ok(DaySelection::Days(vec![7])));
+        assert_eq!("1..=3".parse(), Ok(DaySelection::Days(vec![1, 2, 3])));
+        assert_eq!("1..3".parse(), Ok(DaySelection::Days(vec![1, 2])));
+        assert_eq!(
+            "22,3,7,2..=3".parse(),
+            Ok(DaySelection::Days(vec![2, 3, 7, 22]))
+        );
+
+        assert!("".parse::<DaySelection>().is_err());
+        assert!("3..=1".parse::<DaySelection>().is_err());
+        assert!("1..1".parse::<DaySelection>().is_err());
+        assert!("one".parse::<DaySelection>().is_err());
+    }
+
+    #[test]
+    fn resolve() {
+        assert_eq!(DaySelection::All.resolve([3, 1, 2]), vec![1, 2, 3]);
+        assert_eq!(DaySelection::Days(vec![5]).resolve([1, 2]), vec![5]);
+    }
+}
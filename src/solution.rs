@@ -0,0 +1,215 @@
+use std::{
+    fmt::Display,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::{Error, Result},
+    params::Params,
+    visualize::Frame,
+};
+
+/// A single day's puzzle, split into parsing and the two parts so that the
+/// answers can be returned rather than printed.
+pub trait Solution {
+    /// The day of the puzzle, attached to any error it returns.
+    const DAY: u8;
+
+    /// The `-p` keys that `parse_with` reads. Any other key is an error.
+    const PARAMS: &'static [&'static str] = &[];
+
+    type Parsed;
+    type Part1: Display;
+    type Part2: Display;
+
+    fn parse(&self, lines: Vec<String>) -> Result<Self::Parsed>;
+
+    /// Parses the input along with any `-p key=value` settings. Days that
+    /// take settings override this and have `parse` use the defaults.
+    fn parse_with(&self, lines: Vec<String>, _params: &Params) -> Result<Self::Parsed> {
+        self.parse(lines)
+    }
+
+    fn part1(&self, parsed: &Self::Parsed) -> Result<Self::Part1>;
+
+    fn part2(&self, parsed: &Self::Parsed) -> Result<Self::Part2>;
+
+    /// Lines about how the answers were reached, printed after them but
+    /// never checked against the recorded answers.
+    fn details(&self, _parsed: &Self::Parsed) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Renders the parsed input as `format`, or `None` if the day has no
+    /// such rendering.
+    fn export(&self, _parsed: &Self::Parsed, _format: Export) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Runs the simulation behind the answers, handing each step to `show`,
+    /// or returns `false` if the day has nothing to animate.
+    fn visualize(&self, _parsed: &Self::Parsed, _show: &mut dyn FnMut(Frame)) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+/// Ways of rendering a day's parsed input to look at, rather than solving it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Export {
+    /// A fully parenthesised infix expression.
+    Infix,
+    /// A Graphviz DOT graph.
+    Dot,
+}
+
+impl Export {
+    pub const VARIANTS: [&'static str; 2] = ["infix", "dot"];
+}
+
+impl FromStr for Export {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str {
+            "infix" => Ok(Export::Infix),
+            "dot" => Ok(Export::Dot),
+            _ => Err(format!("unknown export format '{}'", str)),
+        }
+    }
+}
+
+impl Display for Export {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Export::Infix => write!(f, "infix"),
+            Export::Dot => write!(f, "dot"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answers {
+    pub part1: String,
+    pub part2: String,
+    /// See [`Solution::details`].
+    pub details: Vec<String>,
+}
+
+/// Wall-clock time spent in each phase of a [`Solution`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timings {
+    pub parse: Duration,
+    pub part1: Duration,
+    pub part2: Duration,
+}
+
+/// Object safe view of a [`Solution`] so that days with different answer
+/// types can be stored together.
+pub trait Program {
+    /// The `-p` keys the day takes.
+    fn params(&self) -> &'static [&'static str];
+
+    fn solve_timed(&self, lines: Vec<String>, params: &Params) -> Result<(Answers, Timings)>;
+
+    fn solve(&self, lines: Vec<String>) -> Result<Answers> {
+        self.solve_timed(lines, &Params::new())
+            .map(|(answers, _)| answers)
+    }
+
+    fn export(&self, lines: Vec<String>, params: &Params, format: Export)
+        -> Result<Option<String>>;
+
+    fn visualize(
+        &self,
+        lines: Vec<String>,
+        params: &Params,
+        show: &mut dyn FnMut(Frame),
+    ) -> Result<bool>;
+}
+
+impl<S: Solution> Program for S {
+    fn params(&self) -> &'static [&'static str] {
+        S::PARAMS
+    }
+
+    fn export(
+        &self,
+        lines: Vec<String>,
+        params: &Params,
+        format: Export,
+    ) -> Result<Option<String>> {
+        let for_day = |e: Error| e.for_day(S::DAY);
+
+        params.check_keys(S::PARAMS).map_err(for_day)?;
+        let parsed = self.parse_with(lines, params).map_err(for_day)?;
+        Solution::export(self, &parsed, format).map_err(for_day)
+    }
+
+    fn visualize(
+        &self,
+        lines: Vec<String>,
+        params: &Params,
+        show: &mut dyn FnMut(Frame),
+    ) -> Result<bool> {
+        let for_day = |e: Error| e.for_day(S::DAY);
+
+        params.check_keys(S::PARAMS).map_err(for_day)?;
+        let parsed = self.parse_with(lines, params).map_err(for_day)?;
+        Solution::visualize(self, &parsed, show).map_err(for_day)
+    }
+
+    fn solve_timed(&self, lines: Vec<String>, params: &Params) -> Result<(Answers, Timings)> {
+        let for_day = |e: Error| e.for_day(S::DAY);
+
+        params.check_keys(S::PARAMS).map_err(for_day)?;
+
+        let start = Instant::now();
+        let parsed = self.parse_with(lines, params).map_err(for_day)?;
+        let parse = start.elapsed();
+
+        let start = Instant::now();
+        let part1 = self.part1(&parsed).map_err(for_day)?.to_string();
+        let part1_time = start.elapsed();
+
+        let start = Instant::now();
+        let part2 = self.part2(&parsed).map_err(for_day)?.to_string();
+        let part2_time = start.elapsed();
+
+        let details = self.details(&parsed).map_err(for_day)?;
+
+        Ok((
+            Answers {
+                part1,
+                part2,
+                details,
+            },
+            Timings {
+                parse,
+                part1: part1_time,
+                part2: part2_time,
+            },
+        ))
+    }
+}
+
+/// Solves `input`, normally an example from the puzzle statement, panicking
+/// with the error if it fails.
+#[cfg(test)]
+pub(crate) fn example<S: Solution>(solution: S, input: &str) -> (String, String) {
+    example_with(solution, input, &Params::new())
+}
+
+/// Like [`example`], with the settings the example needs.
+#[cfg(test)]
+pub(crate) fn example_with<S: Solution>(
+    solution: S,
+    input: &str,
+    params: &Params,
+) -> (String, String) {
+    let lines = input.lines().map(String::from).collect();
+    match solution.solve_timed(lines, params) {
+        Ok((answers, _)) => (answers.part1, answers.part2),
+        Err(e) => panic!("{}", e),
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    io::{self, Write},
+    num::NonZeroU32,
+    str::FromStr,
+    thread,
+    time::Duration,
+};
+
+use crate::days::{
+    grid::{Bounds, Grid},
+    point::Point2d,
+};
+
+/// One step of a simulation, drawn as characters.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub grid: Grid<char>,
+    /// Where things are happening, which a cropped view keeps in sight.
+    pub focus: Option<Point2d>,
+}
+
+impl Frame {
+    pub fn new(grid: Grid<char>) -> Self {
+        Frame { grid, focus: None }
+    }
+
+    pub fn focused_on(mut self, focus: Point2d) -> Self {
+        self.focus = Some(focus);
+        self
+    }
+}
+
+/// The most of a frame to show at once, parsed from `<width>x<height>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Viewport {
+    /// The part of `bounds` to show, centred on `focus` where it can be.
+    fn crop(&self, bounds: &Bounds, focus: Option<&Point2d>) -> Bounds {
+        let centre = focus.cloned().unwrap_or_else(|| {
+            Point2d::new(
+                (bounds.min.x() + bounds.max.x()) / 2,
+                (bounds.min.y() + bounds.max.y()) / 2,
+            )
+        });
+
+        let span = |centre: i32, min: i32, max: i32, size: i32| {
+            let size = size.min(max - min + 1);
+            let start = (centre - size / 2).clamp(min, max - size + 1);
+            (start, start + size - 1)
+        };
+        let (min_x, max_x) = span(centre.x(), bounds.min.x(), bounds.max.x(), self.width);
+        let (min_y, max_y) = span(centre.y(), bounds.min.y(), bounds.max.y(), self.height);
+
+        Bounds::new(Point2d::new(min_x, min_y), Point2d::new(max_x, max_y))
+    }
+}
+
+impl FromStr for Viewport {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let size = |s: &str| s.parse().ok().filter(|&n: &i32| n > 0);
+        str.split_once('x')
+            .and_then(|(width, height)| Some((size(width)?, size(height)?)))
+            .map(|(width, height)| Viewport { width, height })
+            .ok_or_else(|| format!("expected <width>x<height>, got '{}'", str))
+    }
+}
+
+/// Plays frames back in a terminal.
+#[derive(Debug, Clone, Copy)]
+pub struct Animation {
+    pub fps: NonZeroU32,
+    pub viewport: Option<Viewport>,
+}
+
+impl Animation {
+    /// Clears the terminal, draws `frame` and waits until the next one is due.
+    pub fn show(&self, frame: &Frame) {
+        print!("\x1b[H\x1b[2J{}", self.render(frame));
+        io::stdout().flush().ok();
+        thread::sleep(Duration::from_secs_f64(1.0 / self.fps.get() as f64));
+    }
+
+    /// The rows of `frame` within the viewport.
+    pub fn render(&self, frame: &Frame) -> String {
+        let Some(bounds) = frame.grid.bounds() else {
+            return String::new();
+        };
+        let window = match &self.viewport {
+            Some(viewport) => viewport.crop(&bounds, frame.focus.as_ref()),
+            None => bounds,
+        };
+
+        (window.min.y()..=window.max.y())
+            .map(|y| {
+                (window.min.x()..=window.max.x())
+                    .map(|x| *frame.grid.get(&Point2d::new(x, y)).unwrap_or(&' '))
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU32;
+
+    use super::{Animation, Frame, Viewport};
+    use crate::days::{grid::Grid, point::Point2d};
+
+    #[test]
+    fn crops_around_focus() {
+        let lines: Vec<_> = ["abcde", "fghij", "klmno", "pqrst"]
+            .map(String::from)
+            .to_vec();
+        let grid = Grid::parse(&lines, |c| Ok(Some(c))).unwrap();
+        let mut animation = Animation {
+            fps: NonZeroU32::new(10).unwrap(),
+            viewport: None,
+        };
+
+        assert_eq!(
+            animation.render(&Frame::new(grid.clone())),
+            "abcde\nfghij\nklmno\npqrst\n"
+        );
+
+        animation.viewport = Some("3x2".parse().unwrap());
+        assert_eq!(animation.render(&Frame::new(grid.clone())), "bcd\nghi\n");
+        let frame = Frame::new(grid.clone()).focused_on(Point2d::new(0, 0));
+        assert_eq!(animation.render(&frame), "abc\nfgh\n");
+        let frame = Frame::new(grid).focused_on(Point2d::new(4, 3));
+        assert_eq!(animation.render(&frame), "mno\nrst\n");
+
+        animation.viewport = Some("80x24".parse().unwrap());
+        let frame = Frame::new(Grid::new()).focused_on(Point2d::new(4, 3));
+        assert_eq!(animation.render(&frame), "");
+
+        assert_eq!(
+            "80x24".parse(),
+            Ok(Viewport {
+                width: 80,
+                height: 24
+            })
+        );
+        assert!("80".parse::<Viewport>().is_err());
+        assert!("0x24".parse::<Viewport>().is_err());
+    }
+}